@@ -2,8 +2,12 @@
 #![allow(dead_code)]
 #![allow(improper_ctypes)]
 
-use libc::{c_char, c_int, c_uint, c_void, size_t};
-pub use libc::{input_absinfo, input_event, timeval};
+use libc::{c_char, c_int, c_uint, c_ulong, c_void, size_t};
+pub use libc::{
+    ff_constant_effect, ff_effect, ff_envelope, ff_periodic_effect, ff_replay,
+    ff_rumble_effect, ff_trigger, input_absinfo, input_event, input_id,
+    input_keymap_entry, input_mask, timeval,
+};
 
 pub type __enum_ty = libc::c_int;
 pub type libevdev_read_flag = __enum_ty;
@@ -36,12 +40,79 @@ pub const LIBEVDEV_UINPUT_OPEN_MANAGED: libevdev_uinput_open_mode = -2;
 pub enum libevdev {}
 pub enum libevdev_uinput {}
 
-#[repr(C)]
-pub struct va_list {
-    // TODO
+// ioctl request encoding, see asm-generic/ioctl.h and the arch overrides
+#[cfg(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64"
+))]
+mod ioc {
+    use libc::c_ulong;
+    pub const NONE: c_ulong = 1;
+    pub const READ: c_ulong = 2;
+    pub const WRITE: c_ulong = 4;
+    pub const SIZEBITS: c_ulong = 13;
 }
 
-type libevdev_log_func_t = extern "C" fn(
+#[cfg(not(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64"
+)))]
+mod ioc {
+    use libc::c_ulong;
+    pub const NONE: c_ulong = 0;
+    pub const READ: c_ulong = 2;
+    pub const WRITE: c_ulong = 1;
+    pub const SIZEBITS: c_ulong = 14;
+}
+
+const fn _IOC(dir: c_ulong, type_: u8, nr: c_ulong, size: usize) -> c_ulong {
+    (dir << (16 + ioc::SIZEBITS))
+        | ((size as c_ulong) << 16)
+        | ((type_ as c_ulong) << 8)
+        | nr
+}
+
+const fn _IOR(type_: u8, nr: c_ulong, size: usize) -> c_ulong {
+    _IOC(ioc::READ, type_, nr, size)
+}
+
+const fn _IOW(type_: u8, nr: c_ulong, size: usize) -> c_ulong {
+    _IOC(ioc::WRITE, type_, nr, size)
+}
+
+// evdev ioctls not wrapped by libevdev, see linux/input.h
+pub const EVIOCGID: c_ulong = _IOR(b'E', 0x02, std::mem::size_of::<input_id>());
+// The highest key code, see linux/input-event-codes.h
+pub const KEY_MAX: usize = 0x2ff;
+// EVIOCGKEY(len) with a buffer for all key codes up to KEY_MAX
+pub const EVIOCGKEY: c_ulong = _IOR(b'E', 0x18, KEY_MAX / 8 + 1);
+pub const EVIOCGKEYCODE: c_ulong = _IOR(b'E', 0x04, std::mem::size_of::<[c_uint; 2]>());
+pub const EVIOCGKEYCODE_V2: c_ulong =
+    _IOR(b'E', 0x04, std::mem::size_of::<input_keymap_entry>());
+pub const EVIOCSKEYCODE: c_ulong = _IOW(b'E', 0x04, std::mem::size_of::<[c_uint; 2]>());
+pub const EVIOCSKEYCODE_V2: c_ulong =
+    _IOW(b'E', 0x04, std::mem::size_of::<input_keymap_entry>());
+pub const EVIOCSFF: c_ulong = _IOW(b'E', 0x80, std::mem::size_of::<ff_effect>());
+pub const EVIOCRMFF: c_ulong = _IOW(b'E', 0x81, std::mem::size_of::<c_int>());
+pub const EVIOCGRAB: c_ulong = _IOW(b'E', 0x90, std::mem::size_of::<c_int>());
+pub const EVIOCREVOKE: c_ulong = _IOW(b'E', 0x91, std::mem::size_of::<c_int>());
+pub const EVIOCGMASK: c_ulong = _IOR(b'E', 0x92, std::mem::size_of::<input_mask>());
+pub const EVIOCSMASK: c_ulong = _IOW(b'E', 0x93, std::mem::size_of::<input_mask>());
+
+// A va_list function argument is passed as a pointer on all Linux ABIs,
+// either because va_list is a pointer or array type or because the
+// struct is too large to be passed in registers
+pub type va_list = *mut c_void;
+
+pub type libevdev_log_func_t = extern "C" fn(
     *const libevdev,
     *mut c_void,
     *const c_char,
@@ -51,7 +122,7 @@ type libevdev_log_func_t = extern "C" fn(
     va_list,
 );
 
-type libevdev_device_log_func_t = extern "C" fn(
+pub type libevdev_device_log_func_t = extern "C" fn(
     *const libevdev,
     c_int,
     *mut c_void,
@@ -10,71 +10,6 @@ fn usage() {
     println!("Usage: evtest /path/to/device");
 }
 
-fn print_abs_bits(dev: &Device, axis: &EV_ABS) {
-    let code = EventCode::EV_ABS(axis.clone());
-
-    if !dev.has(code) {
-        return;
-    }
-
-    let abs = dev.abs_info(&code).unwrap();
-
-    println!("\tValue\t{}", abs.value);
-    println!("\tMin\t{}", abs.minimum);
-    println!("\tMax\t{}", abs.maximum);
-    if abs.fuzz != 0 {
-        println!("\tFuzz\t{}", abs.fuzz);
-    }
-    if abs.flat != 0 {
-        println!("\tFlat\t{}", abs.flat);
-    }
-    if abs.resolution != 0 {
-        println!("\tResolution\t{}", abs.resolution);
-    }
-}
-
-fn print_code_bits(dev: &Device, ev_type: &EventType) {
-    for code in EventCodeIterator::new(ev_type) {
-        if !dev.has(code) {
-            continue;
-        }
-
-        println!("    Event code: {}", code);
-        match code {
-            EventCode::EV_ABS(k) => print_abs_bits(dev, &k),
-            _ => (),
-        }
-    }
-}
-
-fn print_bits(dev: &Device) {
-    println!("Supported events:");
-
-    for ev_type in EventTypeIterator::new() {
-        if dev.has(ev_type) {
-            println!("  Event type: {} ", ev_type);
-        }
-
-        match ev_type {
-            EventType::EV_KEY
-            | EventType::EV_REL
-            | EventType::EV_ABS
-            | EventType::EV_LED => print_code_bits(dev, &ev_type),
-            _ => (),
-        }
-    }
-}
-
-fn print_props(dev: &Device) {
-    println!("Properties:");
-
-    for input_prop in InputPropIterator::new() {
-        if dev.has_property(&input_prop) {
-            println!("  Property type: {}", input_prop);
-        }
-    }
-}
-
 fn print_event(ev: &InputEvent) {
     match ev.event_code {
         EventCode::EV_SYN(_) => println!(
@@ -125,19 +60,7 @@ fn main() {
     let u_d = UninitDevice::new().unwrap();
     let d = u_d.set_file(file).unwrap();
 
-    println!(
-        "Input device ID: bus 0x{:x} vendor 0x{:x} product 0x{:x}",
-        d.bustype(),
-        d.vendor_id(),
-        d.product_id()
-    );
-    println!("Evdev version: {:x}", d.driver_version());
-    println!("Input device name: \"{}\"", d.name().unwrap_or(""));
-    println!("Phys location: {}", d.phys().unwrap_or(""));
-    println!("Uniq identifier: {}", d.uniq().unwrap_or(""));
-
-    print_bits(&d);
-    print_props(&d);
+    print!("{}", d.evtest_summary());
 
     let mut a: io::Result<(ReadStatus, InputEvent)>;
     loop {
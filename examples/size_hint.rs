@@ -0,0 +1,55 @@
+//! Times collecting the event codes of every type into vectors, with and
+//! without the `size_hint` of `EventCodeIterator`.
+//!
+//! Run with `cargo run --release --example size_hint`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use evdev_rs::enums::{EventCode, EventType};
+use evdev_rs::{EventCodeIterator, EventTypeIterator};
+
+const ROUNDS: u32 = 2000;
+
+/// Forwards the codes but hides the size hint, like the iterators did
+/// before implementing it
+struct NoHint(EventCodeIterator);
+
+impl Iterator for NoHint {
+    type Item = EventCode;
+
+    fn next(&mut self) -> Option<EventCode> {
+        self.0.next()
+    }
+}
+
+fn time<F: Fn(&EventType) -> Vec<EventCode>>(name: &str, collect: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for ev_type in EventTypeIterator::new() {
+            black_box(collect(&ev_type));
+        }
+    }
+    let elapsed = start.elapsed();
+    println!("{:<24} {:>10.2?} per round", name, elapsed / ROUNDS);
+    elapsed
+}
+
+fn main() {
+    let without = time("without size_hint", |ev_type| {
+        let mut codes = Vec::new();
+        codes.extend(NoHint(EventCodeIterator::new(ev_type)));
+        codes
+    });
+    let with = time("with size_hint", |ev_type| {
+        let codes = EventCodeIterator::new(ev_type);
+        let mut vec = Vec::with_capacity(codes.size_hint().1.unwrap_or(0));
+        vec.extend(codes);
+        vec
+    });
+
+    println!(
+        "speedup: {:.2}x",
+        without.as_secs_f64() / with.as_secs_f64().max(f64::EPSILON)
+    );
+}
@@ -1,7 +1,7 @@
 use std::fs::File;
 
 use evdev_rs::enums::{BusType, EventCode, EventType, EV_KEY, EV_REL, EV_SYN};
-use evdev_rs::{Device, DeviceWrapper, InputEvent, ReadFlag, UInputDevice, UninitDevice};
+use evdev_rs::{Device, DeviceWrapper, ReadFlag, UInputDevice, UninitDevice};
 
 const MOUSE_STEP_X: i32 = 10;
 const MOUSE_STEP_Y: i32 = 10;
@@ -32,7 +32,7 @@ fn main() -> Result<(), std::io::Error> {
     }
 
     // Create virtual device
-    let u = UninitDevice::new().unwrap();
+    let mut u = UninitDevice::new().unwrap();
 
     // Setup device
     // per: https://01.org/linuxgraphics/gfx-docs/drm/input/uinput.html#mouse-movements
@@ -62,28 +62,16 @@ fn main() -> Result<(), std::io::Error> {
         // Map these to mouse events
         println!("Event: {:?}", event);
 
-        // Map direction keys to mouse events
-        let e = match event.event_code {
-            EventCode::EV_KEY(EV_KEY::KEY_RIGHT) => Some((EV_REL::REL_X, MOUSE_STEP_X)),
-            EventCode::EV_KEY(EV_KEY::KEY_LEFT) => Some((EV_REL::REL_X, -MOUSE_STEP_X)),
-            EventCode::EV_KEY(EV_KEY::KEY_UP) => Some((EV_REL::REL_Y, -MOUSE_STEP_Y)),
-            EventCode::EV_KEY(EV_KEY::KEY_DOWN) => Some((EV_REL::REL_Y, MOUSE_STEP_Y)),
-            _ => None,
+        // Map direction keys to mouse movements
+        let (dx, dy) = match event.event_code {
+            EventCode::EV_KEY(EV_KEY::KEY_RIGHT) => (MOUSE_STEP_X, 0),
+            EventCode::EV_KEY(EV_KEY::KEY_LEFT) => (-MOUSE_STEP_X, 0),
+            EventCode::EV_KEY(EV_KEY::KEY_UP) => (0, -MOUSE_STEP_Y),
+            EventCode::EV_KEY(EV_KEY::KEY_DOWN) => (0, MOUSE_STEP_Y),
+            _ => (0, 0),
         };
 
-        // Write mapped event
-        if let Some((e, n)) = e {
-            v.write_event(&InputEvent {
-                time: event.time,
-                event_code: EventCode::EV_REL(e),
-                value: n,
-            })?;
-
-            v.write_event(&InputEvent {
-                time: event.time,
-                event_code: EventCode::EV_SYN(EV_SYN::SYN_REPORT),
-                value: 0,
-            })?;
-        }
+        // Write mapped movement
+        v.move_rel(dx, dy)?;
     }
 }
@@ -0,0 +1,166 @@
+use crate::device::{DeviceWrapper, EnableCodeData};
+use crate::enums::{int_to_input_prop, EventCode, EventType, InputProp, EV_ABS, EV_REP};
+use crate::util::{
+    event_code_to_int, int_to_event_code, EventCodeIterator, EventTypeIterator,
+    InputPropIterator,
+};
+use crate::{AbsInfo, UninitDevice};
+use std::collections::BTreeMap;
+use std::io;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The capabilities of a device as bit arrays, the way the kernel reports
+/// them through EVIOCGBIT and EVIOCGPROP
+///
+/// Bit `n` of a bit array is bit `n % 8` of byte `n / 8`, which is the
+/// layout of the kernel's arrays on little endian machines. The arrays end
+/// with the byte holding the highest set bit, all bits past the end are
+/// unset. Along with the bits, the abs info of every EV_ABS axis and the
+/// EV_REP values are kept, since a device can't be recreated without them.
+///
+/// Name and ids of the device are not part of the bitmap.
+///
+/// ```rust
+/// use evdev_rs::enums::{EventCode, EV_KEY};
+/// use evdev_rs::{DeviceWrapper, UninitDevice};
+///
+/// let dev = UninitDevice::new().unwrap();
+/// dev.enable(EventCode::EV_KEY(EV_KEY::KEY_A)).unwrap();
+///
+/// let bitmap = dev.capability_bitmap();
+/// let copy = UninitDevice::from_capability_bitmap(&bitmap).unwrap();
+/// assert!(copy.has(EventCode::EV_KEY(EV_KEY::KEY_A)));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize), derive(Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CapabilityBitmap {
+    /// the bit array of the supported codes of every supported type
+    pub codes: BTreeMap<EventType, Vec<u8>>,
+    /// the bit array of the properties
+    pub properties: Vec<u8>,
+    pub abs_info: BTreeMap<EV_ABS, AbsInfo>,
+    pub repeat: BTreeMap<EV_REP, i32>,
+}
+
+impl CapabilityBitmap {
+    /// Get the capabilities of the given device
+    pub fn from_device<D: DeviceWrapper>(device: &D) -> CapabilityBitmap {
+        let mut bitmap = CapabilityBitmap::default();
+
+        for prop in InputPropIterator::new() {
+            if device.has_property(&prop) {
+                set_bit(&mut bitmap.properties, prop as u32);
+            }
+        }
+
+        for ev_type in EventTypeIterator::new() {
+            if !device.has_event_type(&ev_type) {
+                continue;
+            }
+
+            let mut bits = Vec::new();
+            for code in EventCodeIterator::new(&ev_type) {
+                if !device.has_event_code(&code) {
+                    continue;
+                }
+                match code {
+                    EventCode::EV_ABS(abs) => {
+                        if let Some(info) = device.abs_info(&code) {
+                            bitmap.abs_info.insert(abs, info);
+                        }
+                    }
+                    EventCode::EV_REP(rep) => {
+                        if let Some(value) = device.event_value(&code) {
+                            bitmap.repeat.insert(rep, value);
+                        }
+                    }
+                    _ => {}
+                }
+                set_bit(&mut bits, event_code_to_int(&code).1);
+            }
+            bitmap.codes.insert(ev_type, bits);
+        }
+
+        bitmap
+    }
+
+    /// Returns `true` if bit `code` is set in the bit array of `ev_type`
+    pub fn has(&self, ev_type: &EventType, code: u32) -> bool {
+        self.codes
+            .get(ev_type)
+            .is_some_and(|bits| test_bit(bits, code))
+    }
+
+    /// The event codes whose bits are set
+    pub(crate) fn event_codes(&self) -> impl Iterator<Item = EventCode> + '_ {
+        self.codes.iter().flat_map(|(ev_type, bits)| {
+            set_bits(bits).map(move |bit| int_to_event_code(*ev_type as u32, bit))
+        })
+    }
+
+    /// The properties whose bits are set
+    pub(crate) fn input_props(&self) -> impl Iterator<Item = InputProp> + '_ {
+        set_bits(&self.properties).filter_map(int_to_input_prop)
+    }
+}
+
+impl UninitDevice {
+    /// Create a new device with the properties and event codes of the
+    /// given bitmap, e.g. one received from another process.
+    ///
+    /// Returns an error with `InvalidInput` if the abs info of an EV_ABS
+    /// axis or the value of an EV_REP code is missing from the bitmap.
+    pub fn from_capability_bitmap(bitmap: &CapabilityBitmap) -> io::Result<UninitDevice> {
+        let device = UninitDevice::new()
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOMEM))?;
+
+        for bit in set_bits(&bitmap.properties) {
+            if let Some(prop) = int_to_input_prop(bit) {
+                device.enable_property(&prop)?;
+            }
+        }
+
+        for (ev_type, bits) in &bitmap.codes {
+            device.enable_event_type(ev_type)?;
+
+            for bit in set_bits(bits) {
+                let code = int_to_event_code(*ev_type as u32, bit);
+                let data = match code {
+                    EventCode::EV_ABS(abs) => bitmap
+                        .abs_info
+                        .get(&abs)
+                        .copied()
+                        .map(EnableCodeData::AbsInfo),
+                    EventCode::EV_REP(rep) => bitmap
+                        .repeat
+                        .get(&rep)
+                        .copied()
+                        .map(EnableCodeData::RepInfo),
+                    _ => None,
+                };
+                device.enable_event_code(&code, data)?;
+            }
+        }
+
+        Ok(device)
+    }
+}
+
+fn set_bit(bits: &mut Vec<u8>, bit: u32) {
+    let byte = bit as usize / 8;
+    if bits.len() <= byte {
+        bits.resize(byte + 1, 0);
+    }
+    bits[byte] |= 1 << (bit % 8);
+}
+
+fn test_bit(bits: &[u8], bit: u32) -> bool {
+    bits.get(bit as usize / 8)
+        .is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+}
+
+fn set_bits(bits: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    (0..bits.len() as u32 * 8).filter(move |bit| test_bit(bits, *bit))
+}
@@ -0,0 +1,44 @@
+use crate::device::DeviceWrapper;
+use crate::enums::{EventCode, EventType, EV_ABS};
+use crate::util::EventCodeIterator;
+use crate::AbsInfo;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The axis calibration of a device, i.e. the abs info of its EV_ABS axes
+///
+/// With the `serde` feature enabled this can be serialized, e.g. to JSON,
+/// to persist a calibration and apply it again later with
+/// [Device::apply_calibration](crate::Device::apply_calibration), or
+/// saved and loaded as JSON directly with
+/// [Device::save_calibration](crate::Device::save_calibration) and
+/// [Device::load_calibration](crate::Device::load_calibration).
+#[cfg_attr(feature = "serde", derive(Serialize), derive(Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Calibration {
+    pub axes: BTreeMap<EV_ABS, AbsInfo>,
+}
+
+impl Calibration {
+    /// Get the calibration of all EV_ABS axes of the given device
+    ///
+    /// ABS_MT_SLOT is not part of the calibration since the kernel doesn't
+    /// allow changing it.
+    pub fn from_device<D: DeviceWrapper>(device: &D) -> Calibration {
+        let mut calibration = Calibration::default();
+
+        for code in EventCodeIterator::new(&EventType::EV_ABS) {
+            let abs = match code {
+                EventCode::EV_ABS(abs) if abs != EV_ABS::ABS_MT_SLOT => abs,
+                _ => continue,
+            };
+            if let Some(info) = device.abs_info(&code) {
+                calibration.axes.insert(abs, info);
+            }
+        }
+
+        calibration
+    }
+}
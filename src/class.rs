@@ -0,0 +1,108 @@
+use crate::enums::{int_to_ev_key, EventCode, InputProp, EV_ABS, EV_KEY, EV_REL};
+
+/// The kinds of devices that can be checked for with
+/// [DeviceWrapper::meets](crate::DeviceWrapper::meets)
+///
+/// Each class requires the event codes and properties a consumer such as
+/// libinput needs at a minimum to use the device as that kind of device,
+/// similar to the heuristics of udev's input_id builtin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceClass {
+    /// The keys KEY_ESC to KEY_S, i.e. the top rows of a keyboard
+    Keyboard,
+    /// REL_X, REL_Y and BTN_LEFT
+    Pointer,
+    /// ABS_X, ABS_Y, BTN_TOUCH and BTN_TOOL_FINGER
+    Touchpad,
+    /// ABS_X, ABS_Y, BTN_TOUCH and INPUT_PROP_DIRECT
+    Touchscreen,
+    /// ABS_X, ABS_Y and BTN_TRIGGER
+    Joystick,
+    /// ABS_X, ABS_Y and BTN_SOUTH
+    Gamepad,
+    /// ABS_X, ABS_Y and BTN_TOOL_PEN
+    Tablet,
+}
+
+impl DeviceClass {
+    /// The event codes a device needs to have to be of this class
+    pub fn required_codes(&self) -> Vec<EventCode> {
+        let abs_xy = [
+            EventCode::EV_ABS(EV_ABS::ABS_X),
+            EventCode::EV_ABS(EV_ABS::ABS_Y),
+        ];
+        let keys: &[EV_KEY] = match self {
+            DeviceClass::Keyboard => {
+                return (EV_KEY::KEY_ESC as u32..=EV_KEY::KEY_S as u32)
+                    .filter_map(int_to_ev_key)
+                    .map(EventCode::EV_KEY)
+                    .collect();
+            }
+            DeviceClass::Pointer => {
+                return vec![
+                    EventCode::EV_REL(EV_REL::REL_X),
+                    EventCode::EV_REL(EV_REL::REL_Y),
+                    EventCode::EV_KEY(EV_KEY::BTN_LEFT),
+                ];
+            }
+            DeviceClass::Touchpad => &[EV_KEY::BTN_TOUCH, EV_KEY::BTN_TOOL_FINGER],
+            DeviceClass::Touchscreen => &[EV_KEY::BTN_TOUCH],
+            DeviceClass::Joystick => &[EV_KEY::BTN_TRIGGER],
+            DeviceClass::Gamepad => &[EV_KEY::BTN_SOUTH],
+            DeviceClass::Tablet => &[EV_KEY::BTN_TOOL_PEN],
+        };
+
+        abs_xy
+            .iter()
+            .copied()
+            .chain(keys.iter().map(|key| EventCode::EV_KEY(*key)))
+            .collect()
+    }
+
+    /// The properties a device needs to have to be of this class
+    pub fn required_properties(&self) -> Vec<InputProp> {
+        match self {
+            DeviceClass::Touchscreen => vec![InputProp::INPUT_PROP_DIRECT],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The physical layout of a keyboard, as guessed by
+/// [DeviceWrapper::keyboard_layout_hint](crate::DeviceWrapper::keyboard_layout_hint)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyboardLayout {
+    /// The US layout, without the keys of the other layouts
+    Ansi,
+    /// The European layout, with an additional key next to the left shift
+    /// key (KEY_102ND)
+    Iso,
+    /// The Japanese layout, with the yen, ro and conversion keys
+    Jis,
+}
+
+impl KeyboardLayout {
+    /// Guess the layout from the keys a keyboard supports
+    ///
+    /// `has_key` returns whether the keyboard supports a key. Any of the
+    /// Japanese keys KEY_YEN, KEY_RO, KEY_HENKAN, KEY_MUHENKAN and
+    /// KEY_KATAKANAHIRAGANA means JIS, otherwise KEY_102ND means ISO and
+    /// the absence of both means ANSI.
+    pub(crate) fn guess<F: Fn(EV_KEY) -> bool>(has_key: F) -> KeyboardLayout {
+        let jis = [
+            EV_KEY::KEY_YEN,
+            EV_KEY::KEY_RO,
+            EV_KEY::KEY_HENKAN,
+            EV_KEY::KEY_MUHENKAN,
+            EV_KEY::KEY_KATAKANAHIRAGANA,
+        ];
+
+        if jis.iter().any(|key| has_key(*key)) {
+            KeyboardLayout::Jis
+        } else if has_key(EV_KEY::KEY_102ND) {
+            KeyboardLayout::Iso
+        } else {
+            KeyboardLayout::Ansi
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::device::DeviceWrapper;
+use crate::enums::{int_to_bus_type, EventCode, InputProp, EV_ABS};
+use crate::{AbsInfo, Calibration, CapabilityBitmap, DeviceId};
+use std::collections::BTreeSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The capabilities of a device: its name, ids, properties, event codes
+/// and axis ranges
+///
+/// The properties and event codes are kept as a [CapabilityBitmap], the
+/// axis ranges as a [Calibration]. Unlike [DeviceState](crate::DeviceState)
+/// this doesn't hold any values: the `value` of every abs info is 0 and the
+/// repeat values of the bitmap are left empty. Two
+/// descriptions of the same device are equal no matter what state the
+/// device is in, so a description can be stored and compared with
+/// [diff](DeviceDescription::diff) later, e.g. to detect changes after a
+/// firmware update or to check that a uinput clone matches its source
+/// device.
+#[cfg_attr(feature = "serde", derive(Serialize), derive(Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceDescription {
+    pub name: Option<String>,
+    /// the ids of the device, `None` if its bus type is unknown
+    pub id: Option<DeviceId>,
+    /// the supported codes and properties, its abs infos only hold
+    /// ABS_MT_SLOT, which is not part of a calibration
+    pub capabilities: CapabilityBitmap,
+    /// the abs info of every EV_ABS axis but ABS_MT_SLOT, as
+    /// `Calibration::from_device` returns it, so it can be applied with
+    /// `Device::apply_calibration`
+    pub calibration: Calibration,
+}
+
+/// The differences between two [DeviceDescription]s
+///
+/// "Added" means present in the description passed to `diff` but not in
+/// the one it was called on, "removed" the opposite.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DescriptionDiff {
+    pub added_codes: Vec<EventCode>,
+    pub removed_codes: Vec<EventCode>,
+    pub added_properties: Vec<InputProp>,
+    pub removed_properties: Vec<InputProp>,
+    /// axes present in both descriptions with a different abs info, as
+    /// the axis, the old and the new abs info
+    pub changed_abs_info: Vec<(EV_ABS, AbsInfo, AbsInfo)>,
+}
+
+impl DeviceDescription {
+    /// Describe the given device
+    pub fn from_device<D: DeviceWrapper>(device: &D) -> DeviceDescription {
+        let mut capabilities = CapabilityBitmap::from_device(device);
+        capabilities.repeat.clear();
+        for info in capabilities.abs_info.values_mut() {
+            info.value = 0;
+        }
+        let mut calibration = Calibration::from_device(device);
+        for info in calibration.axes.values_mut() {
+            info.value = 0;
+        }
+        capabilities
+            .abs_info
+            .retain(|abs, _| !calibration.axes.contains_key(abs));
+
+        DeviceDescription {
+            name: device.name().map(str::to_owned),
+            id: int_to_bus_type(device.bustype() as u32).map(|bustype| DeviceId {
+                bustype,
+                vendor: device.vendor_id(),
+                product: device.product_id(),
+                version: device.version(),
+            }),
+            capabilities,
+            calibration,
+        }
+    }
+
+    /// List the codes, properties and abs infos that differ between this
+    /// description and `other`
+    ///
+    /// Name and ids are not compared.
+    pub fn diff(&self, other: &DeviceDescription) -> DescriptionDiff {
+        let abs_info = |description: &DeviceDescription, abs| {
+            description
+                .calibration
+                .axes
+                .get(abs)
+                .or_else(|| description.capabilities.abs_info.get(abs))
+                .copied()
+        };
+        let mut changed_abs_info: Vec<_> = self
+            .calibration
+            .axes
+            .keys()
+            .chain(self.capabilities.abs_info.keys())
+            .filter_map(|abs| match (abs_info(self, abs), abs_info(other, abs)) {
+                (Some(old), Some(new)) if new != old => Some((*abs, old, new)),
+                _ => None,
+            })
+            .collect();
+        changed_abs_info.sort_by_key(|(abs, _, _)| *abs);
+
+        let codes: BTreeSet<EventCode> = self.capabilities.event_codes().collect();
+        let other_codes: BTreeSet<EventCode> = other.capabilities.event_codes().collect();
+        let properties: BTreeSet<InputProp> = self.capabilities.input_props().collect();
+        let other_properties: BTreeSet<InputProp> =
+            other.capabilities.input_props().collect();
+
+        DescriptionDiff {
+            added_codes: other_codes.difference(&codes).copied().collect(),
+            removed_codes: codes.difference(&other_codes).copied().collect(),
+            added_properties: other_properties.difference(&properties).copied().collect(),
+            removed_properties: properties
+                .difference(&other_properties)
+                .copied()
+                .collect(),
+            changed_abs_info,
+        }
+    }
+}
+
+impl DescriptionDiff {
+    /// Returns `true` if the descriptions don't differ
+    pub fn is_empty(&self) -> bool {
+        self.added_codes.is_empty()
+            && self.removed_codes.is_empty()
+            && self.added_properties.is_empty()
+            && self.removed_properties.is_empty()
+            && self.changed_abs_info.is_empty()
+    }
+}
@@ -1,16 +1,26 @@
-use crate::{AbsInfo, GrabMode, InputEvent, LedState, ReadFlag, ReadStatus, TimeVal};
+use crate::{
+    AbsInfo, Calibration, CapabilityBitmap, ClockId, DeviceClass, DeviceControl,
+    DeviceId, DeviceReader, DeviceState, EvdevVersion, FfEffect, GrabMode, InputEvent,
+    KeyState, KeyboardLayout, LedState, MtExtents, MtMatrix, NextEvent, ReadFlag,
+    ReadInfo, ReadStatus, TimeVal, Touch, TouchFrame, UInputDevice,
+};
 use libc::{c_int, c_uint, c_void};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
 use std::ffi::CString;
 use std::fs::File;
 use std::fs::OpenOptions;
 use std::io::Read;
 use std::mem::ManuallyDrop;
-use std::os::unix::fs::OpenOptionsExt;
-use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
-use std::path::Path;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use std::{io, ptr};
 
 use crate::enums::*;
+use crate::ff;
+use crate::logging::{device_log_handler, LogPriority};
 use crate::util::*;
 
 use evdev_sys as raw;
@@ -78,6 +88,19 @@ pub enum EnableCodeData {
     RepInfo(i32),
 }
 
+/// Declaration of a synthetic device for use with
+/// [UninitDevice::from_spec](crate::UninitDevice::from_spec)
+///
+/// Each entry of `codes` is enabled with the given data, so EV_ABS codes
+/// must be paired with `EnableCodeData::AbsInfo` and EV_REP codes with
+/// `EnableCodeData::RepInfo`.
+#[derive(Clone, Debug)]
+pub struct DeviceSpec {
+    pub name: String,
+    pub id: DeviceId,
+    pub codes: Vec<(EventCode, Option<EnableCodeData>)>,
+}
+
 /// Abstraction over structs which contain an inner `*mut libevdev`
 pub trait DeviceWrapper: Sized {
     fn raw(&self) -> *mut raw::libevdev;
@@ -143,11 +166,15 @@ pub trait DeviceWrapper: Sized {
         ev_code: &EventCode,
         data: Option<EnableCodeData>,
     ) -> io::Result<()> {
+        // Both must outlive the call to libevdev_enable_event_code
+        let absinfo;
+        let rep;
         let data =
             match ev_code {
                 EventCode::EV_ABS(_) => match data {
                     Some(EnableCodeData::AbsInfo(info)) => {
-                        &info.as_raw() as *const _ as *const c_void
+                        absinfo = info.as_raw();
+                        &absinfo as *const _ as *const c_void
                     }
                     _ => return Err(io::Error::new(
                         io::ErrorKind::InvalidInput,
@@ -156,7 +183,8 @@ pub trait DeviceWrapper: Sized {
                 },
                 EventCode::EV_REP(_) => match data {
                     Some(EnableCodeData::RepInfo(info)) => {
-                        &libc::c_int::from(info) as *const _ as *const c_void
+                        rep = libc::c_int::from(info);
+                        &rep as *const _ as *const c_void
                     }
                     _ => return Err(io::Error::new(
                         io::ErrorKind::InvalidInput,
@@ -271,6 +299,183 @@ pub trait DeviceWrapper: Sized {
         unsafe { raw::libevdev_has_property(self.raw(), *prop as c_uint) != 0 }
     }
 
+    /// Returns `true` if the device has `INPUT_PROP_DIRECT` set, i.e. its
+    /// absolute coordinates map directly to a screen (e.g. a touchscreen).
+    fn is_direct(&self) -> bool {
+        self.has_property(&InputProp::INPUT_PROP_DIRECT)
+    }
+
+    /// Returns `true` if the device has `INPUT_PROP_POINTER` set, i.e. it
+    /// requires an on-screen pointer to be usable (e.g. a touchpad).
+    fn is_pointer(&self) -> bool {
+        self.has_property(&InputProp::INPUT_PROP_POINTER)
+    }
+
+    /// Get the properties of the device, ordered by value.
+    ///
+    /// Together with the `Display` implementation of `InputProp` this gives
+    /// a printable summary of the properties:
+    ///
+    /// ```rust
+    /// use evdev_rs::enums::InputProp;
+    /// use evdev_rs::{DeviceWrapper, UninitDevice};
+    ///
+    /// let dev = UninitDevice::new().unwrap();
+    /// dev.enable_property(&InputProp::INPUT_PROP_BUTTONPAD).unwrap();
+    /// dev.enable_property(&InputProp::INPUT_PROP_POINTER).unwrap();
+    ///
+    /// let props: Vec<_> = dev.properties().iter().map(|p| p.to_string()).collect();
+    /// assert_eq!(props.join(", "), "INPUT_PROP_POINTER, INPUT_PROP_BUTTONPAD");
+    /// ```
+    fn properties(&self) -> Vec<InputProp> {
+        InputPropIterator::new()
+            .filter(|prop| self.has_property(prop))
+            .collect()
+    }
+
+    /// Set the minimum priority of libevdev's log messages for this device.
+    ///
+    /// Unlike the global `logging::set_log_priority`, this only affects the
+    /// messages about this device, e.g. to debug a single misbehaving device
+    /// without flooding the log with the messages of all other devices.
+    /// The messages of this device are passed to the `log` crate instead of
+    /// the global log handler from then on.
+    fn set_log_priority(&self, priority: LogPriority) {
+        unsafe {
+            raw::libevdev_set_device_log_function(
+                self.raw(),
+                device_log_handler,
+                priority as raw::libevdev_log_priority,
+                ptr::null_mut(),
+            );
+        }
+    }
+
+    /// Get the relative axes supported by the device, ordered by code.
+    fn rel_axes(&self) -> Vec<EV_REL> {
+        EventCodeIterator::new(&EventType::EV_REL)
+            .filter(|code| self.has_event_code(code))
+            .filter_map(|code| match code {
+                EventCode::EV_REL(rel) => Some(rel),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the strength of the device's autocenter force can
+    /// be set (FF_AUTOCENTER), as with most racing wheels.
+    fn has_autocenter(&self) -> bool {
+        self.has_event_code(&EventCode::EV_FF(EV_FF::FF_AUTOCENTER))
+    }
+
+    /// Returns `true` if the device has a vertical scroll wheel (REL_WHEEL).
+    ///
+    /// Each REL_WHEEL event is one detent of the wheel.
+    fn has_wheel(&self) -> bool {
+        self.has_event_code(&EventCode::EV_REL(EV_REL::REL_WHEEL))
+    }
+
+    /// Returns `true` if the device reports high-resolution scrolling on its
+    /// vertical wheel (REL_WHEEL_HI_RES).
+    ///
+    /// REL_WHEEL_HI_RES events are in fractions of a detent, 120 being one
+    /// detent. The kernel reports REL_WHEEL as well for such devices, a
+    /// consumer should only handle one of the two.
+    fn has_hi_res_wheel(&self) -> bool {
+        self.has_event_code(&EventCode::EV_REL(EV_REL::REL_WHEEL_HI_RES))
+    }
+
+    /// Returns `true` if both devices support the same properties, event
+    /// types and event codes.
+    ///
+    /// Names, ids, current values and abs info are not compared. This is
+    /// useful to recognize a reconnected device, e.g. to reapply its
+    /// configuration.
+    fn capabilities_match<D: DeviceWrapper>(&self, other: &D) -> bool {
+        InputPropIterator::new()
+            .all(|prop| self.has_property(&prop) == other.has_property(&prop))
+            && EventTypeIterator::new().all(|ev_type| {
+                self.has_event_type(&ev_type) == other.has_event_type(&ev_type)
+                    && EventCodeIterator::new(&ev_type).all(|code| {
+                        self.has_event_code(&code) == other.has_event_code(&code)
+                    })
+            })
+    }
+
+    /// Get all supported event codes grouped by their event type, e.g. to
+    /// show the capabilities of the device in a tree view.
+    ///
+    /// Every supported event type is a key of the map, even if none of its
+    /// codes are supported. The codes of each type are sorted.
+    fn codes_by_type(&self) -> BTreeMap<EventType, Vec<EventCode>> {
+        EventTypeIterator::new()
+            .filter(|ev_type| self.has_event_type(ev_type))
+            .map(|ev_type| {
+                let mut codes: Vec<_> = EventCodeIterator::new(&ev_type)
+                    .filter(|code| self.has_event_code(code))
+                    .collect();
+                codes.sort();
+                (ev_type, codes)
+            })
+            .collect()
+    }
+
+    /// Get the supported codes and properties of the device as bit arrays,
+    /// see [CapabilityBitmap].
+    fn capability_bitmap(&self) -> CapabilityBitmap
+    where
+        Self: Sized,
+    {
+        CapabilityBitmap::from_device(self)
+    }
+
+    /// Returns `true` if the device has all event codes and properties
+    /// required for the given class, e.g. to check up front that a device
+    /// is usable as a pointer.
+    ///
+    /// See `DeviceClass::required_codes` and
+    /// `DeviceClass::required_properties` for what exactly is checked.
+    fn meets(&self, class: &DeviceClass) -> bool {
+        class
+            .required_codes()
+            .iter()
+            .all(|code| self.has_event_code(code))
+            && class
+                .required_properties()
+                .iter()
+                .all(|prop| self.has_property(prop))
+    }
+
+    /// Guess the physical layout of a keyboard from the keys it supports,
+    /// e.g. as a default for the keyboard setup of an installer.
+    ///
+    /// Returns `None` if the device is not a keyboard, see
+    /// `DeviceClass::Keyboard`. The guess is a best effort based on the
+    /// capabilities only, see `KeyboardLayout` for the keys checked. Many
+    /// keyboards announce more keys than they physically have, e.g. the
+    /// built-in keyboards of most laptops announce the keys of every layout
+    /// and are reported as JIS, so treat the result as a hint only.
+    fn keyboard_layout_hint(&self) -> Option<KeyboardLayout> {
+        if !self.meets(&DeviceClass::Keyboard) {
+            return None;
+        }
+
+        Some(KeyboardLayout::guess(|key| {
+            self.has_event_code(&EventCode::EV_KEY(key))
+        }))
+    }
+
+    /// Returns `true` if no event type other than EV_SYN is enabled, e.g. on
+    /// a fresh `UninitDevice`.
+    ///
+    /// libevdev reports EV_SYN as supported by every device, so EV_SYN is
+    /// the only key of `codes_by_type` for such a device, and
+    /// `UInputDevice::create_from_device` refuses to create it.
+    fn is_empty(&self) -> bool {
+        !EventTypeIterator::new()
+            .any(|ev_type| ev_type != EventType::EV_SYN && self.has_event_type(&ev_type))
+    }
+
     /// Returns `true` is the device support this event type and `false` otherwise
     ///
     /// Note: Please use the `has` function instead. This function is only
@@ -299,6 +504,12 @@ pub trait DeviceWrapper: Sized {
         uniq, libevdev_get_uniq
     );
 
+    /// Get device's name for display purposes, `"Unknown Device"` if the
+    /// device has no name.
+    fn display_name(&self) -> &str {
+        self.name().unwrap_or("Unknown Device")
+    }
+
     string_setter!(
         set_name,
         libevdev_set_name,
@@ -330,6 +541,62 @@ pub trait DeviceWrapper: Sized {
         libevdev_set_id_version
     );
 
+    /// Get the device's modalias (e.g. `input:b0003v046DpC52Be0111-e0,1,2,4,k...`)
+    ///
+    /// The modalias is built from the device's ids and capabilities in the
+    /// same format the kernel uses for the `modalias` sysfs attribute, so it
+    /// can be matched against udev hwdb entries and quirk databases. Local
+    /// modifications made through `enable`/`disable` are reflected in the
+    /// result.
+    fn modalias(&self) -> String {
+        use std::fmt::Write;
+
+        let mut modalias = format!(
+            "input:b{:04X}v{:04X}p{:04X}e{:04X}-",
+            self.bustype(),
+            self.vendor_id(),
+            self.product_id(),
+            self.version()
+        );
+        let bits = [
+            ('e', None, 0, EventType::EV_MAX as c_uint),
+            (
+                'k',
+                Some(EventType::EV_KEY),
+                EV_KEY::KEY_MUTE as c_uint,
+                EV_KEY::KEY_MAX as c_uint,
+            ),
+            ('r', Some(EventType::EV_REL), 0, EV_REL::REL_MAX as c_uint),
+            ('a', Some(EventType::EV_ABS), 0, EV_ABS::ABS_MAX as c_uint),
+            ('m', Some(EventType::EV_MSC), 0, EV_MSC::MSC_MAX as c_uint),
+            ('l', Some(EventType::EV_LED), 0, EV_LED::LED_MAX as c_uint),
+            ('s', Some(EventType::EV_SND), 0, EV_SND::SND_MAX as c_uint),
+            ('f', Some(EventType::EV_FF), 0, EV_FF::FF_MAX as c_uint),
+            ('w', Some(EventType::EV_SW), 0, EV_SW::SW_MAX as c_uint),
+        ];
+
+        for &(name, ev_type, min, max) in bits.iter() {
+            modalias.push(name);
+            for code in min..max {
+                let has = unsafe {
+                    match ev_type {
+                        None => raw::libevdev_has_event_type(self.raw(), code),
+                        Some(ev_type) => raw::libevdev_has_event_code(
+                            self.raw(),
+                            ev_type as c_uint,
+                            code,
+                        ),
+                    }
+                };
+                if has != 0 {
+                    let _ = write!(modalias, "{:X},", code);
+                }
+            }
+        }
+
+        modalias
+    }
+
     /// Get the axis info for the given axis, as advertised by the kernel.
     ///
     /// Returns the `AbsInfo` for the given the code or None if the device
@@ -352,6 +619,15 @@ pub trait DeviceWrapper: Sized {
     ///
     /// This function has no effect if `has_event_code` returns false for
     /// this code.
+    ///
+    /// This is a local modification only affecting only this representation of
+    /// this device. The new values are returned by `abs_info` and the
+    /// `abs_*` getters, but events returned by `next_event` are passed on
+    /// as reported by the kernel: their values are neither clamped nor
+    /// scaled to the new range. Reading an event for this code only updates
+    /// the `value` of the abs info, the other fields keep what was set here
+    /// until the device is re-read. To change the range the kernel reports
+    /// for all clients, use `Device::set_kernel_abs_info`.
     fn set_abs_info(&self, code: &EventCode, absinfo: &AbsInfo) {
         let (_, ev_code) = event_code_to_int(code);
 
@@ -381,6 +657,142 @@ pub trait DeviceWrapper: Sized {
         }
     }
 
+    /// Get every supported code along with its current value, ordered by
+    /// type and code.
+    ///
+    /// Unlike [DeviceState] this includes stateless types such as EV_REL,
+    /// for which libevdev reports a value of 0, but not the per-slot values
+    /// of multitouch devices.
+    fn current_state(&self) -> Vec<(EventCode, i32)> {
+        self.current_state_filtered(|_, _| true)
+    }
+
+    /// The events for every code whose current value differs from the
+    /// snapshot `prev`, followed by a `SYN_REPORT`, or no events at all if
+    /// nothing changed.
+    ///
+    /// This is an alternative to reading the event stream for consumers
+    /// that only want to poll for changes periodically. Take a new snapshot
+    /// with `DeviceState::from_device` to get the changes since this call
+    /// next time. See `DeviceState::changes` for the events returned.
+    fn changes_since_snapshot(&self, prev: &DeviceState) -> Vec<InputEvent>
+    where
+        Self: Sized,
+    {
+        prev.changes(&DeviceState::from_device(self))
+    }
+
+    /// Like `current_state`, but only return the codes for which `filter`
+    /// returns `true`, e.g. to dump every key that is held down.
+    ///
+    /// ```rust
+    /// use evdev_rs::enums::{EventCode, EV_KEY};
+    /// use evdev_rs::{DeviceWrapper, UninitDevice};
+    ///
+    /// let dev = UninitDevice::new().unwrap();
+    /// dev.enable(EventCode::EV_KEY(EV_KEY::KEY_A)).unwrap();
+    /// dev.enable(EventCode::EV_KEY(EV_KEY::KEY_B)).unwrap();
+    /// dev.set_event_value(&EventCode::EV_KEY(EV_KEY::KEY_B), 1).unwrap();
+    ///
+    /// let pressed = dev.current_state_filtered(|_, value| value != 0);
+    /// assert_eq!(pressed, vec![(EventCode::EV_KEY(EV_KEY::KEY_B), 1)]);
+    /// ```
+    fn current_state_filtered<F>(&self, filter: F) -> Vec<(EventCode, i32)>
+    where
+        F: Fn(&EventCode, i32) -> bool,
+    {
+        let mut state = Vec::new();
+
+        for ev_type in EventTypeIterator::new() {
+            if !self.has_event_type(&ev_type) {
+                continue;
+            }
+
+            for code in EventCodeIterator::new(&ev_type) {
+                match self.event_value(&code) {
+                    Some(value) if filter(&code, value) => state.push((code, value)),
+                    _ => (),
+                }
+            }
+        }
+
+        state
+    }
+
+    /// Get the current state of every LED supported by the device, e.g. to
+    /// show the NUM/CAPS/SCROLL lock indicators at startup.
+    ///
+    /// Returns `true` for LEDs that are on. The LEDs are ordered by code.
+    fn led_states(&self) -> Vec<(EV_LED, bool)> {
+        EventCodeIterator::new(&EventType::EV_LED)
+            .filter_map(|code| match code {
+                EventCode::EV_LED(led) => Some((led, self.event_value(&code)? != 0)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Check that the device could have produced the given event, e.g. before
+    /// replaying events from an untrusted source through uinput.
+    ///
+    /// Fails with `InvalidInput` if the device doesn't support the event's
+    /// code or, for EV_ABS events, if the value is outside the axis range.
+    /// An ABS_MT_TRACKING_ID of -1, which ends a touch, is always valid.
+    fn validate_event(&self, ev: &InputEvent) -> io::Result<()> {
+        if !self.has_event_code(&ev.event_code) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported event code {}", ev.event_code),
+            ));
+        }
+
+        if ev.event_code == EventCode::EV_ABS(EV_ABS::ABS_MT_TRACKING_ID)
+            && ev.value == -1
+        {
+            return Ok(());
+        }
+
+        match self.abs_info(&ev.event_code) {
+            Some(info) if ev.value < info.minimum || ev.value > info.maximum => {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "value {} of {} is outside of [{}, {}]",
+                        ev.value, ev.event_code, info.minimum, info.maximum
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Get the BTN_TOOL_* code that is currently set, e.g. to tell whether a
+    /// pen or a finger is used on a tablet.
+    ///
+    /// Returns `None` if no tool is in proximity. If several tools are set
+    /// at the same time, the one with the lowest code is returned.
+    fn active_tool(&self) -> Option<EV_KEY> {
+        const TOOLS: [EV_KEY; 12] = [
+            EV_KEY::BTN_TOOL_PEN,
+            EV_KEY::BTN_TOOL_RUBBER,
+            EV_KEY::BTN_TOOL_BRUSH,
+            EV_KEY::BTN_TOOL_PENCIL,
+            EV_KEY::BTN_TOOL_AIRBRUSH,
+            EV_KEY::BTN_TOOL_FINGER,
+            EV_KEY::BTN_TOOL_MOUSE,
+            EV_KEY::BTN_TOOL_LENS,
+            EV_KEY::BTN_TOOL_QUINTTAP,
+            EV_KEY::BTN_TOOL_DOUBLETAP,
+            EV_KEY::BTN_TOOL_TRIPLETAP,
+            EV_KEY::BTN_TOOL_QUADTAP,
+        ];
+
+        TOOLS
+            .iter()
+            .copied()
+            .find(|tool| self.event_value(&EventCode::EV_KEY(*tool)) == Some(1))
+    }
+
     /// Set the value for a given event type and code.
     ///
     /// This only makes sense for some event types, e.g. setting the value for
@@ -499,6 +911,26 @@ pub trait DeviceWrapper: Sized {
         }
     }
 
+    /// Get the maximum of the ABS_MT_SLOT axis, or `None` if the device
+    /// does not provide slots.
+    ///
+    /// This is usually `num_slots - 1`, but libevdev caps the number of
+    /// slots it tracks, so for a device announcing more slots than that
+    /// the maximum is larger. Slots above `num_slots - 1` are not tracked
+    /// and `slot_value` and `set_slot_value` fail for them.
+    fn max_slot(&self) -> Option<i32> {
+        self.abs_info(&EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT))
+            .map(|info| info.maximum)
+    }
+
+    /// Get the range of valid slot indices, `0..num_slots`.
+    ///
+    /// Returns `None` if the device does not provide any slots, like
+    /// `num_slots`.
+    fn slot_range(&self) -> Option<std::ops::Range<u32>> {
+        self.num_slots().map(|slots| 0..slots.max(0) as u32)
+    }
+
     /// Get the currently active slot.
     ///
     /// This may differ from the value an ioctl may return at this time as
@@ -514,6 +946,199 @@ pub trait DeviceWrapper: Sized {
             slots => Some(slots),
         }
     }
+
+    /// Call `f` with every slot index and this device, restoring the
+    /// currently active slot afterwards.
+    ///
+    /// `f` may change the active slot, e.g. by setting ABS_MT_SLOT with
+    /// `set_event_value`, without affecting how later events are applied.
+    /// Nothing is called if the device does not provide any slots.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::enums::{EventCode, EV_ABS};
+    /// use evdev_rs::{Device, DeviceWrapper};
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// let tracking_id = EventCode::EV_ABS(EV_ABS::ABS_MT_TRACKING_ID);
+    /// d.with_each_slot(|slot, d| {
+    ///     d.set_slot_value(slot, &tracking_id, -1).unwrap();
+    /// });
+    /// ```
+    fn with_each_slot<F: FnMut(u32, &Self)>(&self, mut f: F)
+    where
+        Self: Sized,
+    {
+        let (slots, current) = match (self.slot_range(), self.current_slot()) {
+            (Some(slots), Some(current)) => (slots, current),
+            _ => return,
+        };
+
+        for slot in slots {
+            f(slot, self);
+        }
+
+        if self.current_slot() != Some(current) {
+            // The slot was valid before, so setting it again can't fail
+            let _ =
+                self.set_event_value(&EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT), current);
+        }
+    }
+
+    /// Get the active contacts of all slots.
+    ///
+    /// A slot is active if its ABS_MT_TRACKING_ID is not -1. The frame is
+    /// empty if the device does not provide any slots.
+    fn touch_frame(&self) -> TouchFrame {
+        let mut frame = TouchFrame::default();
+
+        for slot in self.slot_range().unwrap_or(0..0) {
+            let value = |abs| self.slot_value(slot, &EventCode::EV_ABS(abs));
+
+            let id = match value(EV_ABS::ABS_MT_TRACKING_ID) {
+                Some(id) if id != -1 => id,
+                _ => continue,
+            };
+
+            frame.touches.push(Touch {
+                slot,
+                id,
+                x: value(EV_ABS::ABS_MT_POSITION_X).unwrap_or(0),
+                y: value(EV_ABS::ABS_MT_POSITION_Y).unwrap_or(0),
+                pressure: value(EV_ABS::ABS_MT_PRESSURE),
+                major: value(EV_ABS::ABS_MT_TOUCH_MAJOR),
+                minor: value(EV_ABS::ABS_MT_TOUCH_MINOR),
+            });
+        }
+
+        frame
+    }
+
+    /// Get the number of fingers currently on the device.
+    ///
+    /// For devices with slots this is the number of slots with an active
+    /// ABS_MT_TRACKING_ID. For other devices, e.g. touchpads that report
+    /// only a single position, the count is taken from the BTN_TOOL_FINGER
+    /// to BTN_TOOL_QUINTTAP keys, falling back to 1 if only BTN_TOUCH is
+    /// down.
+    fn active_touch_count(&self) -> usize {
+        if let Some(slots) = self.slot_range() {
+            let tracking_id = EventCode::EV_ABS(EV_ABS::ABS_MT_TRACKING_ID);
+            return slots
+                .filter(|slot| {
+                    self.slot_value(*slot, &tracking_id)
+                        .is_some_and(|id| id >= 0)
+                })
+                .count();
+        }
+
+        let tools = [
+            (EV_KEY::BTN_TOOL_QUINTTAP, 5),
+            (EV_KEY::BTN_TOOL_QUADTAP, 4),
+            (EV_KEY::BTN_TOOL_TRIPLETAP, 3),
+            (EV_KEY::BTN_TOOL_DOUBLETAP, 2),
+            (EV_KEY::BTN_TOOL_FINGER, 1),
+            (EV_KEY::BTN_TOUCH, 1),
+        ];
+        tools
+            .iter()
+            .find(|(key, _)| {
+                self.event_value(&EventCode::EV_KEY(*key))
+                    .is_some_and(|value| value != 0)
+            })
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Get the current values of all supported ABS_MT_* codes in all slots
+    /// in one [MtMatrix], rather than through one `slot_value` call each.
+    ///
+    /// Returns `None` if the device does not provide any slots.
+    fn mt_matrix(&self) -> Option<MtMatrix> {
+        let slots = self.slot_range()?;
+        let codes: Vec<EV_ABS> = EventCodeIterator::new(&EventType::EV_ABS)
+            .filter(|code| is_mt_code(code) && self.has_event_code(code))
+            .filter_map(|code| match code {
+                EventCode::EV_ABS(abs) => Some(abs),
+                _ => None,
+            })
+            .collect();
+
+        let num_slots = slots.len();
+        let mut values = Vec::with_capacity(num_slots * codes.len());
+        for slot in slots {
+            values.extend(
+                codes.iter().map(|abs| {
+                    self.slot_value(slot, &EventCode::EV_ABS(*abs)).unwrap_or(0)
+                }),
+            );
+        }
+
+        Some(MtMatrix::new(num_slots, codes, values))
+    }
+
+    /// Get the coordinate space of the touches of the device.
+    ///
+    /// The extents are taken from ABS_MT_POSITION_X and ABS_MT_POSITION_Y,
+    /// or from ABS_X and ABS_Y if the device doesn't support both MT axes.
+    /// Returns `None` if the device supports neither pair.
+    fn mt_extents(&self) -> Option<MtExtents> {
+        let info = |abs| self.abs_info(&EventCode::EV_ABS(abs));
+
+        let (x, y) = match (
+            info(EV_ABS::ABS_MT_POSITION_X),
+            info(EV_ABS::ABS_MT_POSITION_Y),
+        ) {
+            (Some(x), Some(y)) => (x, y),
+            _ => (info(EV_ABS::ABS_X)?, info(EV_ABS::ABS_Y)?),
+        };
+
+        Some(MtExtents {
+            x_min: x.minimum,
+            x_max: x.maximum,
+            x_resolution: x.resolution,
+            y_min: y.minimum,
+            y_max: y.maximum,
+            y_resolution: y.resolution,
+        })
+    }
+
+    /// Get the current value of an EV_ABS axis in millimeters.
+    ///
+    /// The value is divided by the resolution of the axis, which the kernel
+    /// defines in units per mm for the position axes. Returns `None` if the
+    /// code is not supported or the axis has no resolution set. Note that
+    /// for rotational axes such as ABS_RX the resolution is in units per
+    /// radian instead.
+    fn abs_value_mm(&self, code: &EventCode) -> Option<f32> {
+        let info = self.abs_info(code)?;
+        if info.resolution == 0 {
+            return None;
+        }
+
+        Some(self.event_value(code)? as f32 / info.resolution as f32)
+    }
+
+    /// Get the width and height of the device's sensor area in millimeters,
+    /// e.g. of a touchpad.
+    ///
+    /// The size is the range of the axes of [mt_extents](Self::mt_extents)
+    /// divided by their resolution, so it is measured on ABS_MT_POSITION_X
+    /// and ABS_MT_POSITION_Y if the device supports both, and on ABS_X and
+    /// ABS_Y otherwise. Returns `None` if the device supports neither pair or
+    /// the axes have no resolution.
+    fn physical_size_mm(&self) -> Option<(f32, f32)> {
+        let extents = self.mt_extents()?;
+        let length = |min: i32, max: i32, resolution: i32| {
+            if resolution <= 0 {
+                return None;
+            }
+            Some((max as i64 - min as i64) as f32 / resolution as f32)
+        };
+
+        Some((
+            length(extents.x_min, extents.x_max, extents.x_resolution)?,
+            length(extents.y_min, extents.y_max, extents.y_resolution)?,
+        ))
+    }
 }
 
 /// Opaque struct representing an evdev device with no backing file
@@ -550,6 +1175,11 @@ impl UninitDevice {
     /// If the device changed and you need to re-read a device, use `Device::new_from_file` method.
     /// If you need to change the file after
     /// closing and re-opening the same device, use `change_file`.
+    ///
+    /// libevdev reads the initial state of the device while setting the
+    /// file, so the values returned by `event_value`, `slot_value` and
+    /// `abs_info` are the current values of the device as soon as this
+    /// function returns.
     pub fn set_file(self, file: File) -> io::Result<Device> {
         // Don't call UninitDevice's destructor so we can reuse the inner libevdev
         let leak = ManuallyDrop::new(self);
@@ -558,6 +1188,14 @@ impl UninitDevice {
             0 => Ok(Device {
                 file,
                 raw: leak.raw,
+                grabbed: false,
+                sync_pending: Cell::new(false),
+                needs_resync: Cell::new(false),
+                last_read: Cell::new(None),
+                queued: Cell::new(false),
+                read_buffer: DEFAULT_READ_BUFFER,
+                sync_handler: RefCell::new(None),
+                pressed_at_grab: Vec::new(),
             }),
             error => Err(io::Error::from_raw_os_error(-error)),
         }
@@ -572,6 +1210,151 @@ impl UninitDevice {
     pub fn set_fd(self, file: File) -> io::Result<Device> {
         self.set_file(file)
     }
+
+    /// Set the file for this struct like `set_file` and return a snapshot
+    /// of the initial state of the device along with the device.
+    pub fn set_file_with_snapshot(self, file: File) -> io::Result<(Device, DeviceState)> {
+        let device = self.set_file(file)?;
+        let state = DeviceState::from_device(&device);
+        Ok((device, state))
+    }
+
+    /// Create a new device with the name, id and event codes declared in
+    /// `spec`, e.g. to create a virtual device in a test.
+    ///
+    /// ```rust
+    /// use evdev_rs::enums::{BusType, EventCode, EV_ABS, EV_KEY};
+    /// use evdev_rs::{AbsInfo, DeviceId, DeviceSpec, EnableCodeData, UninitDevice};
+    ///
+    /// let stick = AbsInfo {
+    ///     value: 0,
+    ///     minimum: -32768,
+    ///     maximum: 32767,
+    ///     fuzz: 16,
+    ///     flat: 128,
+    ///     resolution: 0,
+    /// };
+    /// let spec = DeviceSpec {
+    ///     name: "test gamepad".to_owned(),
+    ///     id: DeviceId {
+    ///         bustype: BusType::BUS_USB,
+    ///         vendor: 0x045e,
+    ///         product: 0x028e,
+    ///         version: 0x0110,
+    ///     },
+    ///     codes: vec![
+    ///         (EventCode::EV_KEY(EV_KEY::BTN_SOUTH), None),
+    ///         (EventCode::EV_ABS(EV_ABS::ABS_X), Some(EnableCodeData::AbsInfo(stick))),
+    ///         (EventCode::EV_ABS(EV_ABS::ABS_Y), Some(EnableCodeData::AbsInfo(stick))),
+    ///     ],
+    /// };
+    /// let device = UninitDevice::from_spec(&spec).unwrap();
+    /// ```
+    pub fn from_spec(spec: &DeviceSpec) -> io::Result<UninitDevice> {
+        let mut device = UninitDevice::new()
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOMEM))?;
+
+        device.set_name(&spec.name);
+        device.set_bustype(spec.id.bustype as u16);
+        device.set_vendor_id(spec.id.vendor);
+        device.set_product_id(spec.id.product);
+        device.set_version(spec.id.version);
+
+        for (code, data) in &spec.codes {
+            device.enable_event_code(code, *data)?;
+        }
+
+        Ok(device)
+    }
+
+    /// Create a new device which copies the name, ids, properties and the
+    /// event codes of `device` for which `keep` returns `true`.
+    ///
+    /// EV_SYN codes are always copied. The abs info of every kept EV_ABS
+    /// code and the repeat values of kept EV_REP codes are copied as well,
+    /// so the result can be passed directly to
+    /// [UInputDevice::create_from_device](crate::UInputDevice::create_from_device).
+    /// Trimming unneeded EV_ABS axes this way also avoids running into the
+    /// kernel's limit on the number of axes of a uinput device.
+    pub fn clone_from_filtered<D, F>(device: &D, keep: F) -> io::Result<UninitDevice>
+    where
+        D: DeviceWrapper,
+        F: Fn(&EventCode) -> bool,
+    {
+        let mut clone = UninitDevice::new()
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOMEM))?;
+
+        if let Some(name) = device.name() {
+            clone.set_name(name);
+        }
+        if let Some(phys) = device.phys() {
+            clone.set_phys(phys);
+        }
+        if let Some(uniq) = device.uniq() {
+            clone.set_uniq(uniq);
+        }
+        clone.set_bustype(device.bustype());
+        clone.set_vendor_id(device.vendor_id());
+        clone.set_product_id(device.product_id());
+        clone.set_version(device.version());
+
+        for prop in InputPropIterator::new() {
+            if device.has_property(&prop) {
+                clone.enable_property(&prop)?;
+            }
+        }
+
+        for ev_type in EventTypeIterator::new() {
+            if !device.has_event_type(&ev_type) {
+                continue;
+            }
+
+            for code in EventCodeIterator::new(&ev_type) {
+                if !device.has_event_code(&code) {
+                    continue;
+                }
+                if ev_type != EventType::EV_SYN && !keep(&code) {
+                    continue;
+                }
+
+                let data = match code {
+                    EventCode::EV_ABS(_) => {
+                        device.abs_info(&code).map(EnableCodeData::AbsInfo)
+                    }
+                    EventCode::EV_REP(_) => {
+                        device.event_value(&code).map(EnableCodeData::RepInfo)
+                    }
+                    _ => None,
+                };
+                clone.enable_event_code(&code, data)?;
+            }
+        }
+
+        Ok(clone)
+    }
+
+    /// Enable the given event type and every code of it.
+    ///
+    /// EV_ABS and EV_REP codes need data passed to `enable_event_code`
+    /// that can't be guessed, e.g. enabling an axis with a range of 0 to 0
+    /// makes the kernel reject a uinput device created from it. Those types
+    /// are rejected with `InvalidInput`, enable their codes one by one
+    /// instead.
+    pub fn enable_all_codes(&self, ev_type: &EventType) -> io::Result<()> {
+        if let EventType::EV_ABS | EventType::EV_REP = ev_type {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "EV_ABS and EV_REP codes need to be enabled with their data",
+            ));
+        }
+
+        self.enable_event_type(ev_type)?;
+        for code in EventCodeIterator::new(ev_type) {
+            self.enable_event_code(&code, None)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl Drop for UninitDevice {
@@ -598,8 +1381,24 @@ impl std::fmt::Debug for UninitDevice {
 pub struct Device {
     file: File,
     raw: *mut raw::libevdev,
+    grabbed: bool,
+    sync_pending: Cell<bool>,
+    needs_resync: Cell<bool>,
+    last_read: Cell<Option<(u32, c_int)>>,
+    // libevdev may still hold events it read from the file but didn't
+    // return yet, which a raw read would skip
+    queued: Cell<bool>,
+    read_buffer: usize,
+    sync_handler: RefCell<Option<SyncHandler>>,
+    pressed_at_grab: Vec<EV_KEY>,
 }
 
+/// A callback set with `Device::on_sync`
+type SyncHandler = Box<dyn FnMut(&[InputEvent]) + Send>;
+
+/// The default number of events `Device::read_raw_batch` reads at once
+const DEFAULT_READ_BUFFER: usize = 64;
+
 unsafe impl Send for Device {}
 
 impl DeviceWrapper for Device {
@@ -637,6 +1436,14 @@ impl Device {
             0 => Ok(Device {
                 file,
                 raw: libevdev,
+                grabbed: false,
+                sync_pending: Cell::new(false),
+                needs_resync: Cell::new(false),
+                last_read: Cell::new(None),
+                queued: Cell::new(false),
+                read_buffer: DEFAULT_READ_BUFFER,
+                sync_handler: RefCell::new(None),
+                pressed_at_grab: Vec::new(),
             }),
             error => Err(io::Error::from_raw_os_error(-error)),
         }
@@ -652,6 +1459,22 @@ impl Device {
         Self::new_from_file(file)
     }
 
+    /// Initialize a new libevdev device on top of a file descriptor owned by
+    /// someone else, e.g. an external event loop.
+    ///
+    /// The returned `BorrowedDevice` never closes `fd`, dropping it only
+    /// frees the libevdev context. The same rules about `O_NONBLOCK` as for
+    /// `new_from_file` apply.
+    pub fn new_from_borrowed_fd(fd: BorrowedFd<'_>) -> io::Result<BorrowedDevice<'_>> {
+        let mut libevdev = std::ptr::null_mut();
+        let result = unsafe { raw::libevdev_new_from_fd(fd.as_raw_fd(), &mut libevdev) };
+
+        match result {
+            0 => Ok(BorrowedDevice { fd, raw: libevdev }),
+            error => Err(io::Error::from_raw_os_error(-error)),
+        }
+    }
+
     /// Opens a device with the given path as the location of devnode
     ///
     /// The devnode file is opened with `O_NONBLOCK` and all the pending
@@ -679,11 +1502,75 @@ impl Device {
         }
     }
 
+    /// Opens a device like `new_from_path`, but fails if the ids of the
+    /// device don't match `expected`.
+    ///
+    /// Event nodes are renumbered when devices are reconnected, so a path
+    /// remembered from before may refer to a different device. Checking the
+    /// id guards against grabbing or reconfiguring the wrong device. The
+    /// error has the kind `InvalidData` if the ids don't match.
+    pub fn open_matching<P: AsRef<Path>>(
+        path: P,
+        expected: &DeviceId,
+    ) -> io::Result<Device> {
+        let device = Device::new_from_path(path)?;
+
+        if device.bustype() != expected.bustype as u16
+            || device.vendor_id() != expected.vendor
+            || device.product_id() != expected.product
+            || device.version() != expected.version
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the device id does not match the expected id",
+            ));
+        }
+
+        Ok(device)
+    }
+
+    /// Opens a device like `new_from_path` and sets the clock for the event
+    /// timestamps right away.
+    ///
+    /// The kernel discards the events queued before the clock changed, so
+    /// every event read from the device has a timestamp of `clock`.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::{ClockId, Device, ReadFlag};
+    ///
+    /// let d = Device::open_with_clock("/dev/input/event0", ClockId::Monotonic).unwrap();
+    /// let (_, ev) = d.next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING).unwrap();
+    /// println!("{}.{:06}", ev.time.tv_sec, ev.time.tv_usec);
+    /// ```
+    pub fn open_with_clock<P: AsRef<Path>>(
+        path: P,
+        clock: ClockId,
+    ) -> io::Result<Device> {
+        let device = Device::new_from_path(path)?;
+        device.set_clock_id(clock as i32)?;
+        Ok(device)
+    }
+
     /// Returns the file associated with the device
     pub fn file(&self) -> &File {
         &self.file
     }
 
+    /// Returns `true` if the file of the device is in non-blocking mode,
+    /// i.e. has `O_NONBLOCK` set.
+    ///
+    /// `next_event` should only be called with `ReadFlag::BLOCKING` if this
+    /// returns `false`, which can be the case if the device was created
+    /// from a `File` opened elsewhere.
+    pub fn is_nonblocking(&self) -> io::Result<bool> {
+        let flags = unsafe { libc::fcntl(self.file.as_raw_fd(), libc::F_GETFL) };
+
+        match flags {
+            -1 => Err(io::Error::last_os_error()),
+            flags => Ok(flags & libc::O_NONBLOCK != 0),
+        }
+    }
+
     #[deprecated(
         since = "0.5.0",
         note = "Prefer `file`. This function can easily be misused. Calling
@@ -727,7 +1614,8 @@ impl Device {
     /// # }
     /// ```
     /// After changing the file, the device is assumed ungrabbed and a caller must
-    /// call libevdev_grab() again.
+    /// call libevdev_grab() again. `needs_resync` returns `true` until the
+    /// device has been synced with `ReadFlag::FORCE_SYNC`.
     pub fn change_file(&mut self, file: File) -> io::Result<File> {
         let result = unsafe { raw::libevdev_change_fd(self.raw, file.as_raw_fd()) };
 
@@ -735,6 +1623,8 @@ impl Device {
             0 => {
                 let mut file = file;
                 std::mem::swap(&mut file, &mut self.file);
+                self.grabbed = false;
+                self.needs_resync.set(true);
                 Ok(file)
             }
             error => Err(io::Error::from_raw_os_error(-error)),
@@ -752,6 +1642,46 @@ impl Device {
         Ok(())
     }
 
+    /// Discard all local modifications, e.g. made with `enable`, `disable`,
+    /// `set_event_value` or `set_abs_info`, and read the capabilities and
+    /// state of the device from the kernel again.
+    ///
+    /// This replaces the libevdev context of the device, so other settings
+    /// of this representation of the device such as the log priority are
+    /// reset as well, and any pending sync is discarded. The clock set with
+    /// `set_clock_id` is kept, it is a setting of the file in the kernel
+    /// and the file stays the same.
+    /// A grabbed device is ungrabbed and grabbed again, other clients may
+    /// receive events in between.
+    pub fn reset_to_kernel(&mut self) -> io::Result<()> {
+        let grabbed = self.grabbed;
+        let pressed = self.pressed_at_grab.clone();
+        if grabbed {
+            self.grab(GrabMode::Ungrab)?;
+        }
+
+        let mut libevdev = std::ptr::null_mut();
+        let result =
+            unsafe { raw::libevdev_new_from_fd(self.file.as_raw_fd(), &mut libevdev) };
+        if result == 0 {
+            unsafe { raw::libevdev_free(self.raw) };
+            self.raw = libevdev;
+            self.sync_pending.set(false);
+            self.needs_resync.set(false);
+            self.queued.set(false);
+        }
+
+        if grabbed {
+            self.grab(GrabMode::Grab)?;
+            self.pressed_at_grab = pressed;
+        }
+
+        match result {
+            0 => Ok(()),
+            error => Err(io::Error::from_raw_os_error(-error)),
+        }
+    }
+
     /// Grab or ungrab the device through a kernel EVIOCGRAB.
     ///
     /// This prevents other clients (including kernel-internal ones such as
@@ -762,51 +1692,789 @@ impl Device {
     /// A grab is an operation tied to a file descriptor, not a device. If a
     /// client changes the file descriptor with Device::change_file(), it must
     /// also re-issue a grab with libevdev_grab().
+    ///
+    /// The keys held down when the device is grabbed are recorded, see
+    /// `pressed_keys_at_grab`.
     pub fn grab(&mut self, grab: GrabMode) -> io::Result<()> {
+        let grabbed = matches!(grab, GrabMode::Grab);
+        // Queried before grabbing, so a failure leaves the device alone
+        let pressed = match (grabbed, self.grabbed) {
+            (true, false) => self.kernel_pressed_keys()?,
+            (true, true) => self.pressed_at_grab.clone(),
+            (false, _) => Vec::new(),
+        };
         let result = unsafe { raw::libevdev_grab(self.raw, grab as c_int) };
 
         match result {
-            0 => Ok(()),
+            0 => {
+                self.grabbed = grabbed;
+                self.pressed_at_grab = pressed;
+                Ok(())
+            }
             error => Err(io::Error::from_raw_os_error(-error)),
         }
     }
 
-    /// Check if there are events waiting for us.
-    ///
-    /// This function does not consume an event and may not access the device
-    /// file at all. If there are events queued internally this function will
-    /// return true. If the internal queue is empty, this function will poll
-    /// the file descriptor for data.
+    /// The keys that were held down when the device was last grabbed with
+    /// `grab`, empty if the device is not grabbed.
     ///
-    /// This is a convenience function for simple processes, most complex programs
-    /// are expected to use select(2) or poll(2) on the file descriptor. The kernel
-    /// guarantees that if data is available, it is a multiple of sizeof(struct
-    /// input_event), and thus calling `next_event` when select(2) or
-    /// poll(2) return is safe. You do not need `has_event_pending` if
-    /// you're using select(2) or poll(2).
-    pub fn has_event_pending(&self) -> bool {
-        unsafe { raw::libevdev_has_event_pending(self.raw) > 0 }
+    /// The releases of these keys are only sent to the grabbing client, so
+    /// other clients that saw them being pressed consider them held down,
+    /// e.g. a modifier appears stuck. See `release_stuck_keys`.
+    pub fn pressed_keys_at_grab(&self) -> Vec<EV_KEY> {
+        self.pressed_at_grab.clone()
     }
 
-    /// Return the driver version of a device already intialize with `set_file`
-    pub fn driver_version(&self) -> i32 {
-        unsafe { raw::libevdev_get_driver_version(self.raw) as i32 }
+    /// Write a release of every key in `pressed_keys_at_grab` followed by a
+    /// SYN_REPORT to `uinput`, e.g. the device an interceptor forwards the
+    /// events of this device to.
+    ///
+    /// The keys are released even if they were released on this device
+    /// since the grab, as releasing a key that isn't held down has no
+    /// effect.
+    pub fn release_stuck_keys(&self, uinput: &UInputDevice) -> io::Result<()> {
+        if self.pressed_at_grab.is_empty() {
+            return Ok(());
+        }
+
+        let mut events: Vec<InputEvent> = self
+            .pressed_at_grab
+            .iter()
+            .map(|key| InputEvent::key(*key, KeyState::Released))
+            .collect();
+        events.push(InputEvent::syn_report());
+        Ok(uinput.write_events(&events)?)
     }
 
-    /// Set the device's EV_ABS axis to the value defined in the abs
-    /// parameter. This will be written to the kernel.
-    pub fn set_kernel_abs_info(&self, code: &EventCode, absinfo: &AbsInfo) {
-        let (_, ev_code) = event_code_to_int(code);
+    /// The keys held down according to the kernel through a kernel
+    /// EVIOCGKEY, including those of events not read yet
+    fn kernel_pressed_keys(&self) -> io::Result<Vec<EV_KEY>> {
+        if !self.has_event_type(&EventType::EV_KEY) {
+            return Ok(Vec::new());
+        }
 
-        unsafe {
-            raw::libevdev_kernel_set_abs_info(
-                self.raw,
+        // KEY_MAX + 1 is a multiple of the word size, so the buffer has the
+        // length EVIOCGKEY passes to the kernel
+        let mut bits = vec![0 as libc::c_ulong; bits_to_words(raw::KEY_MAX + 1)];
+        let result = unsafe {
+            libc::ioctl(
+                self.file.as_raw_fd(),
+                raw::EVIOCGKEY as _,
+                bits.as_mut_ptr(),
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(EventCodeIterator::new(&EventType::EV_KEY)
+            .filter_map(|code| match code {
+                EventCode::EV_KEY(key) if test_bit(&bits, key as usize) => Some(key),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Split the device into a reader and a control, so events can be read
+    /// on one thread while another grabs the device or plays force feedback
+    /// effects.
+    ///
+    /// A `Device` is `Send` but not `Sync`, libevdev is not thread safe, so
+    /// the device can't be shared between threads. The split guarantees:
+    ///
+    /// * the [DeviceReader] keeps the libevdev context and is the only one
+    ///   using it, reading events and the cached state needs no locking
+    /// * the [DeviceControl] only issues ioctls and writes on its own
+    ///   duplicate of the file descriptor, which the kernel serializes
+    ///   against the reads of the reader, it is `Sync` and its grab state is
+    ///   behind a lock
+    /// * both refer to the same open file, so a grab through the control
+    ///   applies to the reader, and effects uploaded through the control
+    ///   are kept until both are dropped
+    ///
+    /// libevdev isn't told about a grab through the control, so turn the
+    /// reader back into a device with `DeviceReader::into_inner`, which
+    /// takes the control to bring the grab state of the device in line.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::{Device, GrabMode, ReadFlag};
+    /// use std::sync::Arc;
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// let (reader, control) = d.split().unwrap();
+    /// let control = Arc::new(control);
+    ///
+    /// let grabber = Arc::clone(&control);
+    /// std::thread::spawn(move || grabber.grab(GrabMode::Grab).unwrap());
+    /// while let Ok((_, ev)) = reader.next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING) {
+    ///     println!("{:?}", ev);
+    /// }
+    /// ```
+    pub fn split(self) -> io::Result<(DeviceReader, DeviceControl)> {
+        let file = self.file.try_clone()?;
+        let control = DeviceControl::new(file, self.grabbed, self.has_autocenter());
+        Ok((DeviceReader::new(self), control))
+    }
+
+    /// Make `grab` and libevdev agree with the grab state of `control`,
+    /// after the device was split
+    pub(crate) fn sync_grab(&mut self, control: &DeviceControl) -> io::Result<()> {
+        match (self.grabbed, control.is_grabbed()) {
+            (true, false) => {
+                // The kernel grab is gone already, changing the fd resets
+                // the grab state of libevdev without an EVIOCGRAB
+                unsafe { raw::libevdev_change_fd(self.raw, self.file.as_raw_fd()) };
+                self.grabbed = false;
+                self.pressed_at_grab.clear();
+                Ok(())
+            }
+            (false, true) => {
+                // libevdev can only grab with its own EVIOCGRAB, which fails
+                // with EBUSY while the grab of the control is held
+                control.grab(GrabMode::Ungrab)?;
+                self.grab(GrabMode::Grab)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Grab the device and create a uinput device with the same
+    /// capabilities, e.g. for tools that intercept and modify the events
+    /// of a device.
+    ///
+    /// The uinput device copies the name, ids, properties, event codes and
+    /// abs info of this device. It is created before the device is grabbed,
+    /// so the device is left alone if creating the uinput device fails.
+    pub fn into_interceptor(mut self) -> io::Result<(Device, UInputDevice)> {
+        let uinput = UInputDevice::create_from_device(&self)?;
+        self.grab(GrabMode::Grab)?;
+        Ok((self, uinput))
+    }
+
+    /// Revoke all access to the device through its file with a kernel
+    /// EVIOCREVOKE.
+    ///
+    /// Once revoked, the file can never be used to read events from the device
+    /// again; reads and most ioctls fail with `ENODEV`. This is meant for
+    /// session managers that hand out device files and need to be sure a
+    /// client can no longer read input after losing access. Revoking is
+    /// irreversible, the device must be reopened to be used again.
+    pub fn revoke(&mut self) -> io::Result<()> {
+        let result =
+            unsafe { libc::ioctl(self.file.as_raw_fd(), raw::EVIOCREVOKE as _, 0) };
+
+        match result {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Upload a force feedback effect to the device through a kernel
+    /// EVIOCSFF and return the id the kernel assigned to it.
+    ///
+    /// The device must support EV_FF and the type of the effect. Once
+    /// uploaded, the effect is started with `play_ff_effect` and stays on
+    /// the device until it is removed with `erase_ff_effect` or the file is
+    /// closed.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::{Device, FfEffect, FfEffectKind, FfReplay, FfTrigger};
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// let rumble = FfEffect {
+    ///     kind: FfEffectKind::Rumble {
+    ///         strong_magnitude: 0x8000,
+    ///         weak_magnitude: 0,
+    ///     },
+    ///     direction: 0,
+    ///     trigger: FfTrigger::default(),
+    ///     replay: FfReplay {
+    ///         length: 500,
+    ///         delay: 0,
+    ///     },
+    /// };
+    /// let id = d.upload_ff_effect(&rumble).unwrap();
+    /// d.play_ff_effect(id, 1).unwrap();
+    /// ```
+    pub fn upload_ff_effect(&self, effect: &FfEffect) -> io::Result<i16> {
+        ff::upload(&self.file, effect)
+    }
+
+    /// Remove a force feedback effect uploaded with `upload_ff_effect` from
+    /// the device through a kernel EVIOCRMFF.
+    pub fn erase_ff_effect(&self, id: i16) -> io::Result<()> {
+        ff::erase(&self.file, id)
+    }
+
+    /// Play an uploaded force feedback effect `count` times, or stop it if
+    /// `count` is 0, by writing an EV_FF event with the effect id as code
+    /// to the device.
+    ///
+    /// The device file must have been opened for writing.
+    pub fn play_ff_effect(&self, id: i16, count: i32) -> io::Result<()> {
+        ff::play(&self.file, id, count)
+    }
+
+    /// Set the strength of the force pulling the device back to its center,
+    /// from 0 (off) to 0xffff (full strength), by writing an
+    /// EV_FF(FF_AUTOCENTER) event to the device.
+    ///
+    /// Returns an error with `ENOTSUP` if the device doesn't support
+    /// FF_AUTOCENTER, see `has_autocenter`. The device file must have been
+    /// opened for writing.
+    pub fn set_autocenter(&self, strength: u16) -> io::Result<()> {
+        if !self.has_autocenter() {
+            return Err(io::Error::from_raw_os_error(libc::ENOTSUP));
+        }
+
+        ff::set_autocenter(&self.file, strength)
+    }
+
+    /// Get the id of the device from the kernel through a kernel EVIOCGID.
+    ///
+    /// Unlike `bustype`, `vendor_id`, `product_id` and `version`, which
+    /// return the values cached by libevdev and changed by the matching
+    /// setters, this always queries the kernel.
+    pub fn kernel_device_id(&self) -> io::Result<DeviceId> {
+        let mut id = raw::input_id {
+            bustype: 0,
+            vendor: 0,
+            product: 0,
+            version: 0,
+        };
+        let result =
+            unsafe { libc::ioctl(self.file.as_raw_fd(), raw::EVIOCGID as _, &mut id) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let bustype = int_to_bus_type(id.bustype as u32).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "unknown bus type")
+        })?;
+        Ok(DeviceId {
+            bustype,
+            vendor: id.vendor,
+            product: id.product,
+            version: id.version,
+        })
+    }
+
+    /// Get the keycode the given scancode is mapped to through a kernel
+    /// EVIOCGKEYCODE.
+    ///
+    /// The keycode is returned as the raw kernel value since it does not
+    /// necessarily have a matching `EV_KEY` variant.
+    pub fn keycode(&self, scancode: u32) -> io::Result<u32> {
+        self.keycode_ioctl(raw::EVIOCGKEYCODE_V2, raw::EVIOCGKEYCODE, scancode, 0)
+    }
+
+    /// Map the given scancode to `keycode` through a kernel EVIOCSKEYCODE.
+    ///
+    /// The mapping is changed in the driver, so unlike remapping events in
+    /// userspace it affects every client of the device, and it persists
+    /// until the device is removed or the mapping is changed again.
+    pub fn set_keycode(&self, scancode: u32, keycode: EV_KEY) -> io::Result<()> {
+        self.keycode_ioctl(
+            raw::EVIOCSKEYCODE_V2,
+            raw::EVIOCSKEYCODE,
+            scancode,
+            keycode as u32,
+        )?;
+        Ok(())
+    }
+
+    fn keycode_ioctl(
+        &self,
+        request_v2: libc::c_ulong,
+        request: libc::c_ulong,
+        scancode: u32,
+        keycode: u32,
+    ) -> io::Result<u32> {
+        let mut entry = raw::input_keymap_entry {
+            flags: 0,
+            len: std::mem::size_of::<u32>() as u8,
+            index: 0,
+            keycode,
+            scancode: [0; 32],
+        };
+        entry.scancode[..4].copy_from_slice(&scancode.to_ne_bytes());
+
+        let fd = self.file.as_raw_fd();
+        let result = unsafe { libc::ioctl(fd, request_v2 as _, &mut entry) };
+        if result >= 0 {
+            return Ok(entry.keycode);
+        }
+
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::ENOTTY) {
+            return Err(error);
+        }
+
+        // Kernels before 2.6.37 only support the original ioctl taking
+        // the scancode and keycode as two unsigned ints
+        let mut codes: [c_uint; 2] = [scancode, keycode];
+        let result = unsafe { libc::ioctl(fd, request as _, codes.as_mut_ptr()) };
+        match result {
+            0 => Ok(codes[1]),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Set which event codes of the given type are delivered to this file
+    /// through a kernel EVIOCSMASK.
+    ///
+    /// Only the events with codes in `codes` are reported through this file
+    /// afterwards, all other codes of `ev_type` are dropped by the kernel.
+    /// Unlike `grab`, this only affects this file, other clients still
+    /// receive all events. By default all codes are delivered, passing an
+    /// empty slice suppresses all events of this type, e.g. all `EV_MSC`
+    /// events.
+    ///
+    /// The mask of `EV_SYN` is the kernel's mask of event types, with
+    /// `EV_MAX + 1` bits: only the events of the types of the codes in
+    /// `codes` are delivered, see `set_event_type_mask`. EV_SYN events
+    /// themselves are never filtered.
+    ///
+    /// The event mask is available since Linux 4.4.
+    pub fn set_event_mask(
+        &self,
+        ev_type: &EventType,
+        codes: &[EventCode],
+    ) -> io::Result<()> {
+        let bits = event_mask_bits(ev_type)?;
+        let mut mask = vec![0 as libc::c_ulong; bits_to_words(bits)];
+
+        for code in codes {
+            let (code_type, code) = event_code_to_int(code);
+            if *ev_type == EventType::EV_SYN && (code_type as usize) < bits {
+                set_bit(&mut mask, code_type as usize);
+                continue;
+            }
+            if code_type != *ev_type as c_uint || code as usize >= bits {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "event code does not match the event type of the mask",
+                ));
+            }
+            set_bit(&mut mask, code as usize);
+        }
+
+        self.event_mask_ioctl(raw::EVIOCSMASK, ev_type, &mut mask)
+    }
+
+    /// Get the event codes of the given type which are delivered to this
+    /// file, as set through `set_event_mask`.
+    ///
+    /// For `EV_SYN` this is the mask of event types, with the code 0 of each
+    /// delivered type, see `event_type_mask`.
+    pub fn event_mask(&self, ev_type: &EventType) -> io::Result<Vec<EventCode>> {
+        if *ev_type == EventType::EV_SYN {
+            return Ok(self
+                .event_type_mask()?
+                .iter()
+                .map(|ev_type| int_to_event_code(*ev_type as c_uint, 0))
+                .collect());
+        }
+
+        let bits = event_mask_bits(ev_type)?;
+        let mut mask = vec![0 as libc::c_ulong; bits_to_words(bits)];
+
+        self.event_mask_ioctl(raw::EVIOCGMASK, ev_type, &mut mask)?;
+
+        Ok((0..bits)
+            .filter(|&code| test_bit(&mask, code))
+            .map(|code| int_to_event_code(*ev_type as c_uint, code as c_uint))
+            .collect())
+    }
+
+    /// Set which event types are delivered to this file through a kernel
+    /// EVIOCSMASK of the EV_SYN type mask.
+    ///
+    /// The events of all other types are dropped by the kernel, except
+    /// EV_SYN events which are always delivered. The per type masks set
+    /// through `set_event_mask` apply on top of this.
+    pub fn set_event_type_mask(&self, types: &[EventType]) -> io::Result<()> {
+        let bits = event_mask_bits(&EventType::EV_SYN)?;
+        let mut mask = vec![0 as libc::c_ulong; bits_to_words(bits)];
+
+        for ev_type in types {
+            set_bit(&mut mask, *ev_type as usize);
+        }
+
+        self.event_mask_ioctl(raw::EVIOCSMASK, &EventType::EV_SYN, &mut mask)
+    }
+
+    /// Get the event types which are delivered to this file, as set through
+    /// `set_event_type_mask`.
+    pub fn event_type_mask(&self) -> io::Result<Vec<EventType>> {
+        let bits = event_mask_bits(&EventType::EV_SYN)?;
+        let mut mask = vec![0 as libc::c_ulong; bits_to_words(bits)];
+
+        self.event_mask_ioctl(raw::EVIOCGMASK, &EventType::EV_SYN, &mut mask)?;
+
+        Ok((0..bits)
+            .filter(|&bit| test_bit(&mask, bit))
+            .filter_map(|bit| int_to_event_type(bit as u32))
+            .collect())
+    }
+
+    fn event_mask_ioctl(
+        &self,
+        request: libc::c_ulong,
+        ev_type: &EventType,
+        mask: &mut [libc::c_ulong],
+    ) -> io::Result<()> {
+        let input_mask = raw::input_mask {
+            type_: *ev_type as u32,
+            codes_size: std::mem::size_of_val(mask) as u32,
+            codes_ptr: mask.as_mut_ptr() as u64,
+        };
+        let result =
+            unsafe { libc::ioctl(self.file.as_raw_fd(), request as _, &input_mask) };
+
+        match result {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Release the device, ungrabbing it first if it was grabbed through
+    /// `grab`.
+    ///
+    /// Dropping a `Device` frees it as well, but any error from the ungrab
+    /// is lost. Use this function if a clean release needs to be observed.
+    /// The device is freed even if the ungrab fails.
+    pub fn close(mut self) -> io::Result<()> {
+        if self.grabbed {
+            self.grab(GrabMode::Ungrab)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Iterate over the event types supported by this device.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::Device;
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// for ev_type in d.event_types() {
+    ///     println!("Event type: {}", ev_type);
+    /// }
+    /// ```
+    pub fn event_types(&self) -> impl Iterator<Item = EventType> + '_ {
+        EventTypeIterator::new().filter(move |ev_type| self.has_event_type(ev_type))
+    }
+
+    /// Iterate over the EV_ABS axes supported by this device, along with
+    /// their current value and abs info.
+    ///
+    /// The value is the one libevdev has seen last, like `event_value`, and
+    /// the same as the `value` of the abs info. The axes are read as the
+    /// iterator advances, so collect them first if they must all be from
+    /// the same point in time.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::Device;
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// for (axis, value, info) in d.abs_live() {
+    ///     println!("{:?}: {} ({}..{})", axis, value, info.minimum, info.maximum);
+    /// }
+    /// ```
+    pub fn abs_live(&self) -> impl Iterator<Item = (EV_ABS, i32, AbsInfo)> + '_ {
+        EventCodeIterator::new(&EventType::EV_ABS).filter_map(move |code| {
+            match (code, self.abs_info(&code)) {
+                (EventCode::EV_ABS(axis), Some(info)) => Some((axis, info.value, info)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Set how many events a single `read_raw_batch` call reads at most.
+    ///
+    /// The default is 64. Devices with a high event rate, e.g. gaming mice,
+    /// benefit from a larger buffer, while a smaller one saves memory for
+    /// low rate devices. A size of 0 is treated as 1.
+    pub fn with_read_buffer(&mut self, events: usize) {
+        self.read_buffer = events.max(1);
+    }
+
+    /// The number of events a single `read_raw_batch` call reads at most
+    pub fn read_buffer(&self) -> usize {
+        self.read_buffer
+    }
+
+    /// Read a batch of events directly from the device file with a single
+    /// `read(2)`, bypassing libevdev.
+    ///
+    /// At most `read_buffer` events are read. This is faster than calling
+    /// `next_event` for every event, but libevdev's view of the device
+    /// (e.g. `event_value`) is not updated and `SYN_DROPPED` is not handled,
+    /// the caller has to resync itself.
+    ///
+    /// libevdev reads more than one event from the file at once, so after
+    /// `next_event` it may hold events that a raw read would skip. In that
+    /// case this returns an `EBUSY` error without reading anything. To
+    /// switch from `next_event` to raw reads, call `next_event` until it
+    /// returns `EAGAIN` first.
+    ///
+    /// Like `next_event`, this returns an `EAGAIN` error if the file was
+    /// opened with `O_NONBLOCK` and no events are available.
+    pub fn read_raw_batch(&mut self) -> io::Result<Vec<InputEvent>> {
+        // has_event_pending is also true if the file is readable, so it is
+        // only conclusive if it says there are no events
+        if self.queued.get() {
+            if self.has_event_pending() {
+                return Err(io::Error::from_raw_os_error(libc::EBUSY));
+            }
+            self.queued.set(false);
+        }
+
+        let size = std::mem::size_of::<raw::input_event>();
+        let mut buffer = vec![0u8; self.read_buffer * size];
+        let len = (&self.file).read(&mut buffer)?;
+
+        Ok(buffer[..len]
+            .chunks_exact(size)
+            .filter_map(InputEvent::from_bytes)
+            .collect())
+    }
+
+    /// Check if there are events waiting for us.
+    ///
+    /// This function does not consume an event and may not access the device
+    /// file at all. If there are events queued internally this function will
+    /// return true. If the internal queue is empty, this function will poll
+    /// the file descriptor for data.
+    ///
+    /// This is a convenience function for simple processes, most complex programs
+    /// are expected to use select(2) or poll(2) on the file descriptor. The kernel
+    /// guarantees that if data is available, it is a multiple of sizeof(struct
+    /// input_event), and thus calling `next_event` when select(2) or
+    /// poll(2) return is safe. You do not need `has_event_pending` if
+    /// you're using select(2) or poll(2).
+    pub fn has_event_pending(&self) -> bool {
+        unsafe { raw::libevdev_has_event_pending(self.raw) > 0 }
+    }
+
+    /// Read the `name` attribute of the input device from sysfs.
+    ///
+    /// This is usually identical to `name`, but unlike `name` it is not
+    /// cached by libevdev and is not affected by `set_name`.
+    pub fn sysfs_name(&self) -> io::Result<String> {
+        let name = std::fs::read_to_string(self.sysfs_path()?.join("name"))?;
+        Ok(name.trim_end_matches('\n').to_owned())
+    }
+
+    // The sysfs directory of the input device (/sys/class/input/inputN)
+    // this event node belongs to.
+    fn sysfs_path(&self) -> io::Result<PathBuf> {
+        let rdev = self.file.metadata()?.rdev();
+        let path = format!(
+            "/sys/dev/char/{}:{}/device",
+            libc::major(rdev),
+            libc::minor(rdev)
+        );
+        Ok(PathBuf::from(path))
+    }
+
+    /// Return the driver version of a device already intialize with `set_file`
+    pub fn driver_version(&self) -> i32 {
+        unsafe { raw::libevdev_get_driver_version(self.raw) as i32 }
+    }
+
+    /// Return the driver version of the device decoded into its major,
+    /// minor and patch numbers
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::{Device, EvdevVersion};
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// if d.evdev_version() < EvdevVersion::from_raw(0x010001) {
+    ///     println!("evdev {} is too old", d.evdev_version());
+    /// }
+    /// ```
+    pub fn evdev_version(&self) -> EvdevVersion {
+        EvdevVersion::from_raw(self.driver_version())
+    }
+
+    /// A summary of the device in the format of evtest: the ids, name, phys
+    /// and uniq of the device, the supported event codes with the abs info
+    /// of every axis, and the properties.
+    ///
+    /// This is meant for diagnostics, e.g. to attach to a bug report.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::Device;
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// print!("{}", d.evtest_summary());
+    /// ```
+    pub fn evtest_summary(&self) -> String {
+        let mut summary = String::new();
+        self.write_evtest_summary(&mut summary)
+            .expect("writing to a String can't fail");
+        summary
+    }
+
+    fn write_evtest_summary(&self, out: &mut String) -> std::fmt::Result {
+        use std::fmt::Write;
+
+        writeln!(
+            out,
+            "Input device ID: bus 0x{:x} vendor 0x{:x} product 0x{:x}",
+            self.bustype(),
+            self.vendor_id(),
+            self.product_id()
+        )?;
+        writeln!(out, "Evdev version: {}", self.evdev_version())?;
+        writeln!(out, "Input device name: \"{}\"", self.display_name())?;
+        writeln!(out, "Phys location: {}", self.phys().unwrap_or(""))?;
+        writeln!(out, "Uniq identifier: {}", self.uniq().unwrap_or(""))?;
+
+        writeln!(out, "Supported events:")?;
+        for ev_type in self.event_types() {
+            writeln!(out, "  Event type: {} ", ev_type)?;
+            if !matches!(
+                ev_type,
+                EventType::EV_KEY
+                    | EventType::EV_REL
+                    | EventType::EV_ABS
+                    | EventType::EV_LED
+            ) {
+                continue;
+            }
+
+            for code in EventCodeIterator::new(&ev_type) {
+                if !self.has(code) {
+                    continue;
+                }
+                writeln!(out, "    Event code: {}", code)?;
+
+                let abs = match self.abs_info(&code) {
+                    Some(abs) if matches!(code, EventCode::EV_ABS(_)) => abs,
+                    _ => continue,
+                };
+                writeln!(out, "\tValue\t{}", abs.value)?;
+                writeln!(out, "\tMin\t{}", abs.minimum)?;
+                writeln!(out, "\tMax\t{}", abs.maximum)?;
+                if abs.fuzz != 0 {
+                    writeln!(out, "\tFuzz\t{}", abs.fuzz)?;
+                }
+                if abs.flat != 0 {
+                    writeln!(out, "\tFlat\t{}", abs.flat)?;
+                }
+                if abs.resolution != 0 {
+                    writeln!(out, "\tResolution\t{}", abs.resolution)?;
+                }
+            }
+        }
+
+        writeln!(out, "Properties:")?;
+        for input_prop in self.properties() {
+            writeln!(out, "  Property type: {}", input_prop)?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the device's EV_ABS axis to the value defined in the abs
+    /// parameter. This will be written to the kernel.
+    ///
+    /// Unlike `set_abs_info`, this affects every client of the device, e.g.
+    /// the kernel uses the new fuzz to filter the events. libevdev also
+    /// updates its local copy, so `abs_info` returns the new values
+    /// afterwards.
+    pub fn set_kernel_abs_info(&self, code: &EventCode, absinfo: &AbsInfo) {
+        let (_, ev_code) = event_code_to_int(code);
+
+        unsafe {
+            raw::libevdev_kernel_set_abs_info(
+                self.raw,
                 ev_code,
                 &absinfo.as_raw() as *const _,
             );
         }
     }
 
+    /// Apply a calibration, e.g. one previously saved with
+    /// `Calibration::from_device`, to the kernel device like
+    /// `set_kernel_abs_info`.
+    ///
+    /// The current value of each axis is kept, only the range, fuzz, flat
+    /// and resolution are changed. Fails with `InvalidInput` without
+    /// changing anything if the calibration contains an axis the device
+    /// doesn't have.
+    pub fn apply_calibration(&self, calibration: &Calibration) -> io::Result<()> {
+        let mut axes = Vec::with_capacity(calibration.axes.len());
+        for (abs, info) in &calibration.axes {
+            let current = self.abs_info(&EventCode::EV_ABS(*abs)).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("device has no axis {}", EventCode::EV_ABS(*abs)),
+                )
+            })?;
+            axes.push((
+                *abs,
+                AbsInfo {
+                    value: current.value,
+                    ..*info
+                },
+            ));
+        }
+
+        for (abs, info) in axes {
+            let result = unsafe {
+                raw::libevdev_kernel_set_abs_info(
+                    self.raw,
+                    abs as c_uint,
+                    &info.as_raw() as *const _,
+                )
+            };
+            if result != 0 {
+                return Err(io::Error::from_raw_os_error(-result));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the abs info of every EV_ABS axis of the device to `writer` as
+    /// JSON, the serialized [Calibration] of the device.
+    ///
+    /// Only available with the `serde` feature.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::Device;
+    /// use std::fs::File;
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// d.save_calibration(&mut File::create("calibration.json").unwrap())
+    ///     .unwrap();
+    /// d.load_calibration(&mut File::open("calibration.json").unwrap())
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn save_calibration<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, &Calibration::from_device(self))
+            .map_err(io::Error::from)
+    }
+
+    /// Read a calibration written by `save_calibration` from `reader` and
+    /// apply it like `apply_calibration`, through the kernel.
+    ///
+    /// Fails with `InvalidData` if the JSON is not a calibration, and like
+    /// `apply_calibration` with `InvalidInput` without changing anything if
+    /// the device lacks one of the axes. Only available with the `serde`
+    /// feature.
+    #[cfg(feature = "serde")]
+    pub fn load_calibration<R: io::Read>(&self, reader: &mut R) -> io::Result<()> {
+        let calibration: Calibration =
+            serde_json::from_reader(reader).map_err(io::Error::from)?;
+        self.apply_calibration(&calibration)
+    }
+
     /// Turn an LED on or off.
     ///
     /// enabling an LED requires write permissions on the device's file descriptor.
@@ -866,36 +2534,406 @@ impl Device {
     /// e.g. after changing the file descriptor, use the `evdev::FORCE_SYNC` flag.
     /// This triggers an internal sync of the device and `next_event` returns
     /// `ReadStatus::Sync`.
+    ///
+    /// If a handler was set with `on_sync`, a `SYN_DROPPED` is not returned to
+    /// the caller. Instead the state delta is passed to the handler and the
+    /// next regular event is returned.
     pub fn next_event(&self, flags: ReadFlag) -> io::Result<(ReadStatus, InputEvent)> {
-        let mut ev = raw::input_event {
-            time: raw::timeval {
-                tv_sec: 0,
-                tv_usec: 0,
-            },
-            type_: 0,
-            code: 0,
-            value: 0,
-        };
+        match self.poll_event(flags) {
+            NextEvent::Event(event) => Ok((ReadStatus::Success, event)),
+            NextEvent::SyncEvent(event) => Ok((ReadStatus::Sync, event)),
+            NextEvent::WouldBlock => Err(io::Error::from_raw_os_error(libc::EAGAIN)),
+            NextEvent::Error(error) => Err(error),
+        }
+    }
 
-        let result = unsafe {
-            raw::libevdev_next_event(self.raw, flags.bits() as c_uint, &mut ev)
-        };
+    /// Get the next event from the device like `next_event`, but return
+    /// every outcome of the read as a variant of `NextEvent`, so no case
+    /// can be forgotten when matching on it.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::{Device, NextEvent, ReadFlag};
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// loop {
+    ///     match d.poll_event(ReadFlag::NORMAL) {
+    ///         NextEvent::Event(ev) => println!("{:?}", ev),
+    ///         NextEvent::SyncEvent(_) => {
+    ///             while let NextEvent::SyncEvent(ev) = d.poll_event(ReadFlag::SYNC) {
+    ///                 println!("synced {:?}", ev);
+    ///             }
+    ///         }
+    ///         NextEvent::WouldBlock => break,
+    ///         NextEvent::Error(err) => panic!("{}", err),
+    ///     }
+    /// }
+    /// ```
+    pub fn poll_event(&self, flags: ReadFlag) -> NextEvent {
+        let (result, event) = self.read_event(flags.bits());
 
-        let event = InputEvent {
-            time: TimeVal {
-                tv_sec: ev.time.tv_sec,
-                tv_usec: ev.time.tv_usec,
-            },
-            event_code: int_to_event_code(ev.type_ as u32, ev.code as u32),
-            value: ev.value,
-        };
+        if result == raw::LIBEVDEV_READ_STATUS_SYNC
+            && flags.contains(ReadFlag::FORCE_SYNC)
+        {
+            self.needs_resync.set(false);
+        }
+
+        if result == raw::LIBEVDEV_READ_STATUS_SYNC && !flags.contains(ReadFlag::SYNC) {
+            let handled = match self.sync_handler.borrow_mut().as_mut() {
+                Some(handler) => match self.drain_sync() {
+                    Ok(delta) => {
+                        handler(&delta);
+                        true
+                    }
+                    Err(error) => return NextEvent::Error(error),
+                },
+                None => false,
+            };
+            if handled {
+                self.sync_pending.set(false);
+                let bits = flags.bits() & !ReadFlag::FORCE_SYNC.bits();
+                return self.poll_event(ReadFlag::from_bits_retain(bits));
+            }
+        }
 
         match result {
-            raw::LIBEVDEV_READ_STATUS_SUCCESS => Ok((ReadStatus::Success, event)),
-            raw::LIBEVDEV_READ_STATUS_SYNC => Ok((ReadStatus::Sync, event)),
-            error => Err(io::Error::from_raw_os_error(-error)),
+            raw::LIBEVDEV_READ_STATUS_SUCCESS => {
+                self.sync_pending.set(false);
+                NextEvent::Event(event)
+            }
+            raw::LIBEVDEV_READ_STATUS_SYNC => {
+                self.sync_pending.set(true);
+                NextEvent::SyncEvent(event)
+            }
+            error if error == -libc::EAGAIN => {
+                self.sync_pending.set(false);
+                NextEvent::WouldBlock
+            }
+            error => NextEvent::Error(io::Error::from_raw_os_error(-error)),
+        }
+    }
+
+    /// Handle `SYN_DROPPED` events automatically
+    ///
+    /// When `next_event` is called without the `SYNC` flag and encounters a
+    /// `SYN_DROPPED`, or is called with `FORCE_SYNC`, it reads all events of
+    /// the state delta, calls `f` with them and then continues with the next
+    /// regular event as if no sync had happened. This replaces a previously
+    /// set handler.
+    ///
+    /// The handler is called from within `next_event` while the device is
+    /// borrowed, it must not call `on_sync` on the same device, doing so
+    /// panics. Since it has to be `'static`, it cannot borrow the device to
+    /// read events from it either.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::{Device, ReadFlag};
+    ///
+    /// let d = Device::new_from_path("/dev/input/event0").unwrap();
+    /// d.on_sync(|delta| println!("resynced {} events", delta.len()));
+    /// loop {
+    ///     let (_, ev) = d.next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING).unwrap();
+    ///     println!("{:?}", ev);
+    /// }
+    /// ```
+    pub fn on_sync<F: FnMut(&[InputEvent]) + Send + 'static>(&self, f: F) {
+        *self.sync_handler.borrow_mut() = Some(Box::new(f));
+    }
+
+    /// Bring the state of the device up to date and return the events that
+    /// make up the changes, e.g. after a `SYN_DROPPED`.
+    ///
+    /// If a sync is pending, i.e. `next_event` returned the `SYN_DROPPED`,
+    /// its delta is read. Otherwise a sync is forced first, like calling
+    /// `next_event` with `ReadFlag::FORCE_SYNC`. The delta holds an event
+    /// for every key, switch and axis that changed, and for multitouch
+    /// devices the slot changes, including the ABS_MT_TRACKING_ID of touches
+    /// that ended or started, so the caller doesn't have to walk the slots.
+    /// A handler set with `on_sync` is not called.
+    pub fn resync_and_report_changes(&self) -> io::Result<Vec<InputEvent>> {
+        if !self.sync_pending.get() {
+            let (result, _) = self.read_event(ReadFlag::FORCE_SYNC.bits());
+            if result < 0 {
+                return Err(io::Error::from_raw_os_error(-result));
+            }
         }
+
+        let delta = self.drain_sync()?;
+        self.sync_pending.set(false);
+        self.needs_resync.set(false);
+        Ok(delta)
+    }
+
+    /// The flags and the outcome of the last read of an event from the
+    /// device, or `None` if no event has been read yet.
+    ///
+    /// This covers every read through `next_event` and the methods built on
+    /// it, which helps to find out why a read loop keeps getting `EAGAIN`
+    /// or unexpectedly enters sync mode.
+    pub fn last_read_info(&self) -> Option<ReadInfo> {
+        let (flags, result) = self.last_read.get()?;
+        let (status, errno) = match result {
+            raw::LIBEVDEV_READ_STATUS_SUCCESS => (Some(ReadStatus::Success), None),
+            raw::LIBEVDEV_READ_STATUS_SYNC => (Some(ReadStatus::Sync), None),
+            error => (None, Some(-error)),
+        };
+
+        Some(ReadInfo {
+            flags: ReadFlag::from_bits_retain(flags),
+            status,
+            errno,
+        })
+    }
+
+    /// Read the next event from libevdev and record the outcome for
+    /// `last_read_info`
+    fn read_event(&self, flags: u32) -> (c_int, InputEvent) {
+        let (result, event) = next_event_raw(self.raw, flags);
+        self.last_read.set(Some((flags, result)));
+        if result >= 0 {
+            self.queued.set(true);
+        } else if result == -libc::EAGAIN && flags & ReadFlag::SYNC.bits() == 0 {
+            self.queued.set(false);
+        }
+        (result, event)
+    }
+
+    /// Read the events of a pending sync until libevdev reports it is done
+    fn drain_sync(&self) -> io::Result<Vec<InputEvent>> {
+        let mut delta = Vec::new();
+
+        loop {
+            let (result, event) = self.read_event(ReadFlag::SYNC.bits());
+            match result {
+                raw::LIBEVDEV_READ_STATUS_SYNC => delta.push(event),
+                error if error == -libc::EAGAIN => break,
+                error if error < 0 => return Err(io::Error::from_raw_os_error(-error)),
+                _ => break,
+            }
+        }
+
+        Ok(delta)
     }
+
+    /// Read events from the device in a loop and call `handler` for every
+    /// event, until reading fails.
+    ///
+    /// A `SYN_DROPPED` is handled internally, the events of the state delta
+    /// are passed to `handler` instead, like regular events. A handler set
+    /// with `on_sync` takes precedence. With `ReadFlag::BLOCKING` on a file
+    /// opened without `O_NONBLOCK`, this blocks forever waiting for events
+    /// and only returns on errors such as the device being removed.
+    /// Otherwise it returns an `EAGAIN` error once no more events are
+    /// available.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::{Device, ReadFlag};
+    /// use std::fs::File;
+    ///
+    /// let d = Device::new_from_file(File::open("/dev/input/event0").unwrap()).unwrap();
+    /// let err = d
+    ///     .dispatch(ReadFlag::NORMAL | ReadFlag::BLOCKING, |ev| {
+    ///         println!("{:?} {}", ev.event_code, ev.value)
+    ///     })
+    ///     .unwrap_err();
+    /// println!("stopped reading: {}", err);
+    /// ```
+    pub fn dispatch<F: FnMut(InputEvent)>(
+        &self,
+        flags: ReadFlag,
+        mut handler: F,
+    ) -> io::Result<()> {
+        let mut flags = flags;
+
+        loop {
+            match self.next_event(flags)? {
+                (ReadStatus::Success, event) => handler(event),
+                (ReadStatus::Sync, _) => {
+                    self.drain_sync()?.into_iter().for_each(&mut handler);
+                    self.sync_pending.set(false);
+                }
+            }
+            // A forced sync is only needed once
+            flags.remove(ReadFlag::FORCE_SYNC);
+        }
+    }
+
+    /// Get the next event from the device like `next_event`, but return
+    /// `Ok(None)` instead of an `EAGAIN` error if no event is available.
+    ///
+    /// This separates "nothing to read right now" from real errors, e.g. the
+    /// device being removed. In sync mode, `Ok(None)` means that all events
+    /// of the sync delta have been read.
+    pub fn try_next_event(
+        &self,
+        flags: ReadFlag,
+    ) -> io::Result<Option<(ReadStatus, InputEvent)>> {
+        match self.next_event(flags) {
+            Ok(event) => Ok(Some(event)),
+            Err(error) if error.raw_os_error() == Some(libc::EAGAIN) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Get the next event of the sync delta if the device is syncing,
+    /// otherwise the next regular event, or `None` if no event is available
+    pub(crate) fn next_event_or_delta(&self) -> io::Result<Option<InputEvent>> {
+        if self.sync_pending() {
+            if let Some((_, event)) = self.try_next_event(ReadFlag::SYNC)? {
+                return Ok(Some(event));
+            }
+        }
+
+        Ok(self
+            .try_next_event(ReadFlag::NORMAL)?
+            .map(|(_, event)| event))
+    }
+
+    /// Get the next event from the device like `next_event`, but wait at most
+    /// `timeout` for an event to become available.
+    ///
+    /// Returns `Ok(None)` if no event was available before the timeout
+    /// expired. Unlike `next_event` with `ReadFlag::BLOCKING`, this never
+    /// blocks indefinitely, which makes it a good fit for single threaded
+    /// tools that need to do other work while waiting for input.
+    pub fn next_event_timeout(
+        &self,
+        flags: ReadFlag,
+        timeout: Duration,
+    ) -> io::Result<Option<(ReadStatus, InputEvent)>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if !self.has_event_pending() {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                // Round up so we never wake up before the deadline
+                let ms = remaining.as_nanos().div_ceil(1_000_000);
+                let mut pollfd = libc::pollfd {
+                    fd: self.file.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let result = unsafe {
+                    libc::poll(&mut pollfd, 1, ms.min(c_int::MAX as u128) as c_int)
+                };
+                match result {
+                    0 => return Ok(None),
+                    -1 => return Err(io::Error::last_os_error()),
+                    _ => (),
+                }
+            }
+
+            match self.try_next_event(flags)? {
+                Some(event) => return Ok(Some(event)),
+                // Everything read was filtered out, e.g. disabled codes
+                None if Instant::now() >= deadline => return Ok(None),
+                None => (),
+            }
+        }
+    }
+
+    /// Returns `true` if the device is in the middle of a sync.
+    ///
+    /// This is the case after `next_event` returned `ReadStatus::Sync`, either
+    /// for a `SYN_DROPPED` or an event of the sync delta, until the delta has
+    /// been drained by calling `next_event` with `ReadFlag::SYNC` until it
+    /// returns `-EAGAIN`, or until `next_event` returns a regular event again.
+    /// Callers can use this to decide whether to enter their sync loop.
+    pub fn sync_pending(&self) -> bool {
+        self.sync_pending.get()
+    }
+
+    /// Returns `true` if the file was changed with `change_file` and the
+    /// state libevdev keeps for the device may be stale.
+    ///
+    /// This stays `true` until `next_event` has been called with
+    /// `ReadFlag::FORCE_SYNC`, after which the sync delta can be read as
+    /// usual.
+    pub fn needs_resync(&self) -> bool {
+        self.needs_resync.get()
+    }
+
+    /// Read and discard all events that are currently pending, without
+    /// blocking.
+    ///
+    /// If a `SYN_DROPPED` is encountered, the device is synced and the
+    /// events of the state delta are discarded as well. The internal state
+    /// of the device is still updated by the discarded events, only the
+    /// events themselves are dropped.
+    ///
+    /// Returns the number of discarded events.
+    pub fn flush(&self) -> io::Result<usize> {
+        let mut discarded = 0;
+
+        while self.has_event_pending() {
+            match self.next_event(ReadFlag::NORMAL) {
+                Ok((ReadStatus::Success, _)) => discarded += 1,
+                Ok((ReadStatus::Sync, _)) => {
+                    discarded += 1;
+                    loop {
+                        match self.next_event(ReadFlag::SYNC) {
+                            Ok(_) => discarded += 1,
+                            Err(e) if e.raw_os_error() == Some(libc::EAGAIN) => break,
+                            Err(e) => return Err(e),
+                        }
+                    }
+                }
+                Err(e) if e.raw_os_error() == Some(libc::EAGAIN) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(discarded)
+    }
+}
+
+fn next_event_raw(libevdev: *mut raw::libevdev, flags: u32) -> (c_int, InputEvent) {
+    let mut ev = raw::input_event {
+        time: raw::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+        type_: 0,
+        code: 0,
+        value: 0,
+    };
+
+    let result = unsafe { raw::libevdev_next_event(libevdev, flags as c_uint, &mut ev) };
+
+    let event = InputEvent {
+        time: TimeVal {
+            tv_sec: ev.time.tv_sec,
+            tv_usec: ev.time.tv_usec,
+        },
+        event_code: int_to_event_code(ev.type_ as u32, ev.code as u32),
+        value: ev.value,
+    };
+
+    (result, event)
+}
+
+/// The number of bits of the event mask of a type, the mask of EV_SYN is
+/// the mask of event types
+fn event_mask_bits(ev_type: &EventType) -> io::Result<usize> {
+    match ev_type {
+        EventType::EV_SYN => Some(EventType::EV_MAX as u32),
+        _ => EventType::get_max(ev_type),
+    }
+    .map(|max| max as usize + 1)
+    .ok_or_else(|| io::Error::from_raw_os_error(libc::EINVAL))
+}
+
+const BITS_PER_WORD: usize = 8 * std::mem::size_of::<libc::c_ulong>();
+
+fn bits_to_words(bits: usize) -> usize {
+    bits.div_ceil(BITS_PER_WORD)
+}
+
+fn set_bit(bitmap: &mut [libc::c_ulong], bit: usize) {
+    bitmap[bit / BITS_PER_WORD] |= 1 << (bit % BITS_PER_WORD);
+}
+
+fn test_bit(bitmap: &[libc::c_ulong], bit: usize) -> bool {
+    bitmap[bit / BITS_PER_WORD] & (1 << (bit % BITS_PER_WORD)) != 0
 }
 
 impl Drop for Device {
@@ -921,3 +2959,74 @@ impl AsRawFd for Device {
         self.file.as_raw_fd()
     }
 }
+
+/// An evdev device on top of a file descriptor it doesn't own
+///
+/// Created with [Device::new_from_borrowed_fd]. Unlike `Device`, dropping a
+/// `BorrowedDevice` doesn't close the file descriptor, it only frees the
+/// libevdev context.
+pub struct BorrowedDevice<'fd> {
+    fd: BorrowedFd<'fd>,
+    raw: *mut raw::libevdev,
+}
+
+unsafe impl Send for BorrowedDevice<'_> {}
+
+impl DeviceWrapper for BorrowedDevice<'_> {
+    fn raw(&self) -> *mut raw::libevdev {
+        self.raw
+    }
+}
+
+impl BorrowedDevice<'_> {
+    /// Check if there are events waiting for us.
+    ///
+    /// See `Device::has_event_pending`.
+    pub fn has_event_pending(&self) -> bool {
+        unsafe { raw::libevdev_has_event_pending(self.raw) > 0 }
+    }
+
+    /// Get the next event from the device.
+    ///
+    /// See `Device::next_event`.
+    pub fn next_event(&self, flags: ReadFlag) -> io::Result<(ReadStatus, InputEvent)> {
+        let (result, event) = next_event_raw(self.raw, flags.bits());
+
+        match result {
+            raw::LIBEVDEV_READ_STATUS_SUCCESS => Ok((ReadStatus::Success, event)),
+            raw::LIBEVDEV_READ_STATUS_SYNC => Ok((ReadStatus::Sync, event)),
+            error => Err(io::Error::from_raw_os_error(-error)),
+        }
+    }
+}
+
+impl Drop for BorrowedDevice<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            raw::libevdev_free(self.raw);
+        }
+    }
+}
+
+impl std::fmt::Debug for BorrowedDevice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BorrowedDevice")
+            .field("fd", &self.fd)
+            .field("name", &self.name())
+            .field("phys", &self.phys())
+            .field("uniq", &self.uniq())
+            .finish()
+    }
+}
+
+impl AsRawFd for BorrowedDevice<'_> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl AsFd for BorrowedDevice<'_> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd
+    }
+}
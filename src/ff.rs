@@ -0,0 +1,208 @@
+use crate::enums::{EventCode, EventType, EV_FF};
+use crate::util::int_to_event_code;
+use crate::{InputEvent, TimeVal};
+use libc::{c_int, c_uint};
+use std::fs::File;
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::{io, mem, ptr};
+
+use evdev_sys as raw;
+
+/// A force feedback effect that can be uploaded to a device with
+/// [Device::upload_ff_effect](crate::Device::upload_ff_effect)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FfEffect {
+    pub kind: FfEffectKind,
+    /// the direction of the effect, 0x0000 is down, 0x4000 left, 0x8000 up
+    /// and 0xc000 right
+    pub direction: u16,
+    pub trigger: FfTrigger,
+    pub replay: FfReplay,
+}
+
+/// The type specific parameters of a [FfEffect]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FfEffectKind {
+    /// A rumble effect as supported by most gamepads, with the magnitudes
+    /// of the heavy and the light motor
+    Rumble {
+        strong_magnitude: u16,
+        weak_magnitude: u16,
+    },
+    /// A periodic effect, `waveform` is one of FF_SQUARE, FF_TRIANGLE,
+    /// FF_SINE, FF_SAW_UP and FF_SAW_DOWN
+    Periodic {
+        waveform: EV_FF,
+        /// the period of the wave in ms
+        period: u16,
+        magnitude: i16,
+        offset: i16,
+        phase: u16,
+        envelope: FfEnvelope,
+    },
+    /// A constant force
+    Constant { level: i16, envelope: FfEnvelope },
+}
+
+/// When an effect is triggered by a button of the device
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FfTrigger {
+    /// the code of the button triggering the effect, 0 for none
+    pub button: u16,
+    /// the minimum time between two triggers in ms
+    pub interval: u16,
+}
+
+/// How long an effect plays
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FfReplay {
+    /// the duration of the effect in ms
+    pub length: u16,
+    /// the delay before the effect starts in ms
+    pub delay: u16,
+}
+
+/// How the strength of an effect ramps up at its start and down at its end
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FfEnvelope {
+    pub attack_length: u16,
+    pub attack_level: u16,
+    pub fade_length: u16,
+    pub fade_level: u16,
+}
+
+impl FfEnvelope {
+    const fn as_raw(&self) -> raw::ff_envelope {
+        raw::ff_envelope {
+            attack_length: self.attack_length,
+            attack_level: self.attack_level,
+            fade_length: self.fade_length,
+            fade_level: self.fade_level,
+        }
+    }
+}
+
+impl FfEffect {
+    /// Convert the effect into a kernel `struct ff_effect` with the given id
+    ///
+    /// Returns an error with `InvalidInput` if the waveform of a periodic
+    /// effect is not one of the supported waveforms.
+    pub(crate) fn as_raw(&self, id: i16) -> io::Result<raw::ff_effect> {
+        let mut effect: raw::ff_effect = unsafe { mem::zeroed() };
+        effect.id = id;
+        effect.direction = self.direction;
+        effect.trigger = raw::ff_trigger {
+            button: self.trigger.button,
+            interval: self.trigger.interval,
+        };
+        effect.replay = raw::ff_replay {
+            length: self.replay.length,
+            delay: self.replay.delay,
+        };
+
+        // The parameters are a union in the kernel struct, which libc
+        // represents as an array large enough for all of its members.
+        let params = effect.u.as_mut_ptr();
+        match self.kind {
+            FfEffectKind::Rumble {
+                strong_magnitude,
+                weak_magnitude,
+            } => unsafe {
+                effect.type_ = EV_FF::FF_RUMBLE as u16;
+                ptr::write(
+                    params as *mut raw::ff_rumble_effect,
+                    raw::ff_rumble_effect {
+                        strong_magnitude,
+                        weak_magnitude,
+                    },
+                );
+            },
+            FfEffectKind::Periodic {
+                waveform,
+                period,
+                magnitude,
+                offset,
+                phase,
+                envelope,
+            } => {
+                if (waveform as u32) < (EV_FF::FF_SQUARE as u32)
+                    || (waveform as u32) > (EV_FF::FF_SAW_DOWN as u32)
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "unsupported waveform for a periodic effect",
+                    ));
+                }
+                effect.type_ = EV_FF::FF_PERIODIC as u16;
+                unsafe {
+                    ptr::write(
+                        params as *mut raw::ff_periodic_effect,
+                        raw::ff_periodic_effect {
+                            waveform: waveform as u16,
+                            period,
+                            magnitude,
+                            offset,
+                            phase,
+                            envelope: envelope.as_raw(),
+                            custom_len: 0,
+                            custom_data: ptr::null_mut(),
+                        },
+                    );
+                }
+            }
+            FfEffectKind::Constant { level, envelope } => unsafe {
+                effect.type_ = EV_FF::FF_CONSTANT as u16;
+                ptr::write(
+                    params as *mut raw::ff_constant_effect,
+                    raw::ff_constant_effect {
+                        level,
+                        envelope: envelope.as_raw(),
+                    },
+                );
+            },
+        }
+
+        Ok(effect)
+    }
+}
+
+/// Upload an effect through a kernel EVIOCSFF, returning its id
+pub(crate) fn upload(file: &File, effect: &FfEffect) -> io::Result<i16> {
+    let mut effect = effect.as_raw(-1)?;
+    let result =
+        unsafe { libc::ioctl(file.as_raw_fd(), raw::EVIOCSFF as _, &mut effect) };
+
+    match result {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(effect.id),
+    }
+}
+
+/// Remove an uploaded effect through a kernel EVIOCRMFF
+pub(crate) fn erase(file: &File, id: i16) -> io::Result<()> {
+    let result =
+        unsafe { libc::ioctl(file.as_raw_fd(), raw::EVIOCRMFF as _, id as c_int) };
+
+    match result {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Play an uploaded effect `count` times by writing an EV_FF event
+pub(crate) fn play(file: &File, id: i16, count: i32) -> io::Result<()> {
+    let code = int_to_event_code(EventType::EV_FF as c_uint, id as c_uint);
+    write_event(file, &code, count)
+}
+
+/// Set the autocenter strength by writing an EV_FF(FF_AUTOCENTER) event
+pub(crate) fn set_autocenter(file: &File, strength: u16) -> io::Result<()> {
+    let code = EventCode::EV_FF(EV_FF::FF_AUTOCENTER);
+    write_event(file, &code, strength as i32)
+}
+
+fn write_event(mut file: &File, code: &EventCode, value: i32) -> io::Result<()> {
+    let event = InputEvent::new(&TimeVal::new(0, 0), code, value);
+    file.write_all(&event.to_bytes())
+}
@@ -0,0 +1,113 @@
+use crate::enums::{EventCode, EventType, EV_SYN};
+use crate::InputEvent;
+
+/// Adapters to filter a stream of events
+///
+/// Implemented for every iterator over [InputEvent]s, so the adapters
+/// compose with other iterator adapters and with [Frames](crate::Frames).
+///
+/// ```rust,no_run
+/// use evdev_rs::enums::EventType;
+/// use evdev_rs::{Device, EventFilter, ReadFlag};
+///
+/// let d = Device::new_from_path("/dev/input/event0").unwrap();
+/// let events = std::iter::from_fn(|| {
+///     d.next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING)
+///         .ok()
+///         .map(|(_, ev)| ev)
+/// });
+/// for ev in events.only_type(EventType::EV_KEY) {
+///     println!("{} {}", ev.event_code, ev.value);
+/// }
+/// ```
+pub trait EventFilter: Iterator<Item = InputEvent> + Sized {
+    /// Only yield the events of the given type
+    fn only_type(self, ev_type: EventType) -> OnlyType<Self> {
+        OnlyType {
+            events: self,
+            ev_type,
+        }
+    }
+
+    /// Only yield the events with the given code
+    fn only_code(self, code: EventCode) -> OnlyCode<Self> {
+        OnlyCode { events: self, code }
+    }
+
+    /// Don't yield SYN_REPORT events, for consumers that handle every event
+    /// on its own rather than in frames
+    ///
+    /// Other EV_SYN events such as SYN_DROPPED are still yielded, use
+    /// [skip_all_syn](EventFilter::skip_all_syn) to drop those as well.
+    fn skip_syn(self) -> SkipSyn<Self> {
+        SkipSyn {
+            events: self,
+            all: false,
+        }
+    }
+
+    /// Don't yield any EV_SYN events
+    fn skip_all_syn(self) -> SkipSyn<Self> {
+        SkipSyn {
+            events: self,
+            all: true,
+        }
+    }
+}
+
+impl<I: Iterator<Item = InputEvent>> EventFilter for I {}
+
+/// An iterator yielding only the events of one type, created by
+/// [EventFilter::only_type]
+#[derive(Clone, Debug)]
+pub struct OnlyType<I> {
+    events: I,
+    ev_type: EventType,
+}
+
+impl<I: Iterator<Item = InputEvent>> Iterator for OnlyType<I> {
+    type Item = InputEvent;
+
+    fn next(&mut self) -> Option<InputEvent> {
+        let ev_type = self.ev_type;
+        self.events.find(|ev| ev.event_type() == Some(ev_type))
+    }
+}
+
+/// An iterator yielding only the events with one code, created by
+/// [EventFilter::only_code]
+#[derive(Clone, Debug)]
+pub struct OnlyCode<I> {
+    events: I,
+    code: EventCode,
+}
+
+impl<I: Iterator<Item = InputEvent>> Iterator for OnlyCode<I> {
+    type Item = InputEvent;
+
+    fn next(&mut self) -> Option<InputEvent> {
+        let code = self.code;
+        self.events.find(|ev| ev.event_code == code)
+    }
+}
+
+/// An iterator skipping SYN_REPORT or all EV_SYN events, created by
+/// [EventFilter::skip_syn] or [EventFilter::skip_all_syn]
+#[derive(Clone, Debug)]
+pub struct SkipSyn<I> {
+    events: I,
+    all: bool,
+}
+
+impl<I: Iterator<Item = InputEvent>> Iterator for SkipSyn<I> {
+    type Item = InputEvent;
+
+    fn next(&mut self) -> Option<InputEvent> {
+        let all = self.all;
+        self.events.find(|ev| match ev.event_code {
+            EventCode::EV_SYN(EV_SYN::SYN_REPORT) => false,
+            EventCode::EV_SYN(_) => !all,
+            _ => true,
+        })
+    }
+}
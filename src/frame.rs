@@ -0,0 +1,80 @@
+use crate::enums::{EventCode, EV_SYN};
+use crate::InputEvent;
+
+/// Groups a stream of events into frames
+///
+/// Each frame is the list of events up to and including a `SYN_REPORT`.
+/// `SYN_MT_REPORT` events of type A multitouch devices don't end a frame,
+/// use [mt_contacts] to split a frame into its contacts. If the stream
+/// ends in the middle of a frame, the remaining events are returned as a
+/// last frame without a `SYN_REPORT`.
+///
+/// ```rust,no_run
+/// use evdev_rs::{Device, Frames, ReadFlag};
+///
+/// let d = Device::new_from_path("/dev/input/event0").unwrap();
+/// let events = std::iter::from_fn(|| {
+///     d.next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING)
+///         .ok()
+///         .map(|(_, ev)| ev)
+/// });
+/// for frame in Frames::new(events) {
+///     println!("{} events", frame.len());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Frames<I> {
+    events: I,
+}
+
+impl<I: Iterator<Item = InputEvent>> Frames<I> {
+    pub fn new(events: I) -> Frames<I> {
+        Frames { events }
+    }
+}
+
+impl<I: Iterator<Item = InputEvent>> Iterator for Frames<I> {
+    type Item = Vec<InputEvent>;
+
+    fn next(&mut self) -> Option<Vec<InputEvent>> {
+        let mut frame = Vec::new();
+
+        for ev in &mut self.events {
+            let end = ev.event_code == EventCode::EV_SYN(EV_SYN::SYN_REPORT);
+            frame.push(ev);
+            if end {
+                return Some(frame);
+            }
+        }
+
+        if frame.is_empty() {
+            None
+        } else {
+            Some(frame)
+        }
+    }
+}
+
+/// Split a frame of a type A multitouch device into its contacts
+///
+/// Type A devices report each contact as a group of events terminated by
+/// a `SYN_MT_REPORT`. Each returned slice holds the events since the
+/// previous `SYN_MT_REPORT` or the start of the frame, without the
+/// `SYN_MT_REPORT` itself, so non-MT events such as `BTN_TOUCH` reported
+/// before the first contact end up in its slice. Events after the last
+/// `SYN_MT_REPORT`, e.g. the final `SYN_REPORT`, don't belong to a contact
+/// and are not returned.
+pub fn mt_contacts(frame: &[InputEvent]) -> Vec<&[InputEvent]> {
+    let mt_report = EventCode::EV_SYN(EV_SYN::SYN_MT_REPORT);
+    let mut contacts = Vec::new();
+    let mut start = 0;
+
+    for (i, ev) in frame.iter().enumerate() {
+        if ev.event_code == mt_report {
+            contacts.push(&frame[start..i]);
+            start = i + 1;
+        }
+    }
+
+    contacts
+}
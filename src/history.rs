@@ -0,0 +1,72 @@
+use crate::enums::EventCode;
+use crate::InputEvent;
+use std::collections::{HashMap, VecDeque};
+
+/// Keeps the most recent values reported for each event code
+///
+/// This is independent of any device and can be fed from any event
+/// stream, e.g. to implement gesture detection over the last few values
+/// of an axis.
+///
+/// ```rust
+/// use evdev_rs::enums::{EventCode, EV_REL};
+/// use evdev_rs::{InputEvent, TimeVal, ValueHistory};
+///
+/// let mut history = ValueHistory::new(2);
+/// let code = EventCode::EV_REL(EV_REL::REL_X);
+/// for value in 1..=3 {
+///     history.record(&InputEvent::new(&TimeVal::new(0, 0), &code, value));
+/// }
+/// assert_eq!(history.recent(&code), &[2, 3]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ValueHistory {
+    capacity: usize,
+    values: HashMap<EventCode, VecDeque<i32>>,
+}
+
+impl ValueHistory {
+    /// Create a history keeping at most `capacity` values per event code
+    pub fn new(capacity: usize) -> ValueHistory {
+        ValueHistory {
+            capacity,
+            values: HashMap::new(),
+        }
+    }
+
+    /// The maximum number of values kept per event code
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Record the value of the given event, discarding the oldest value
+    /// for its code if the history for that code is full.
+    pub fn record(&mut self, ev: &InputEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let capacity = self.capacity;
+        let values = self
+            .values
+            .entry(ev.event_code)
+            .or_insert_with(|| VecDeque::with_capacity(capacity));
+        if values.len() == capacity {
+            values.pop_front();
+        }
+        values.push_back(ev.value);
+    }
+
+    /// The values recorded for the given code, oldest first.
+    ///
+    /// Returns an empty buffer if no value was recorded for this code.
+    pub fn recent(&self, code: &EventCode) -> &VecDeque<i32> {
+        static EMPTY: VecDeque<i32> = VecDeque::new();
+        self.values.get(code).unwrap_or(&EMPTY)
+    }
+
+    /// Forget all recorded values
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
@@ -0,0 +1,192 @@
+//! Notifications about input devices being added and removed, through
+//! libudev. Only available with the `udev` feature.
+
+use libc::{c_char, c_int};
+use libudev_sys as ffi;
+use std::ffi::{CStr, OsStr};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::PathBuf;
+use std::ptr;
+
+const INPUT: &[u8] = b"input\0";
+const EVENT_NODE_PREFIX: &[u8] = b"/dev/input/event";
+
+/// An event device that was added or removed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HotplugEvent {
+    /// The device node of a new device, e.g. `/dev/input/event5`
+    Added(PathBuf),
+    /// The device node of a device that was removed
+    Removed(PathBuf),
+}
+
+/// Watches for event devices being added and removed
+///
+/// Only `/dev/input/event*` nodes are reported, other nodes of the input
+/// subsystem such as joydev and mousedev nodes are ignored. The monitor
+/// doesn't block, wait for its file descriptor to become readable, e.g.
+/// with `poll`, to be notified of new events.
+///
+/// ```rust,no_run
+/// use evdev_rs::{HotplugEvent, HotplugMonitor};
+///
+/// let monitor = HotplugMonitor::new().unwrap();
+/// for path in monitor.enumerate().unwrap() {
+///     println!("present: {}", path.display());
+/// }
+/// while let Some(event) = monitor.next_event().unwrap() {
+///     match event {
+///         HotplugEvent::Added(path) => println!("added: {}", path.display()),
+///         HotplugEvent::Removed(path) => println!("removed: {}", path.display()),
+///     }
+/// }
+/// ```
+pub struct HotplugMonitor {
+    udev: *mut ffi::udev,
+    monitor: *mut ffi::udev_monitor,
+}
+
+unsafe impl Send for HotplugMonitor {}
+
+impl HotplugMonitor {
+    /// Start watching the input subsystem for new and removed devices
+    pub fn new() -> io::Result<HotplugMonitor> {
+        let udev = unsafe { ffi::udev_new() };
+        if udev.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        let monitor = unsafe {
+            ffi::udev_monitor_new_from_netlink(udev, b"udev\0".as_ptr() as *const c_char)
+        };
+        if monitor.is_null() {
+            let error = io::Error::last_os_error();
+            unsafe { ffi::udev_unref(udev) };
+            return Err(error);
+        }
+
+        // Dropping the monitor releases udev and the monitor on failure
+        let hotplug = HotplugMonitor { udev, monitor };
+        check(unsafe {
+            ffi::udev_monitor_filter_add_match_subsystem_devtype(
+                monitor,
+                INPUT.as_ptr() as *const c_char,
+                ptr::null(),
+            )
+        })?;
+        check(unsafe { ffi::udev_monitor_enable_receiving(monitor) })?;
+
+        Ok(hotplug)
+    }
+
+    /// List the device nodes of the event devices that are present
+    ///
+    /// Create the monitor before enumerating the devices, so a device added
+    /// in between is reported by `next_event` rather than being missed.
+    pub fn enumerate(&self) -> io::Result<Vec<PathBuf>> {
+        let enumerate = unsafe { ffi::udev_enumerate_new(self.udev) };
+        if enumerate.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        let result = (|| {
+            check(unsafe {
+                ffi::udev_enumerate_add_match_subsystem(
+                    enumerate,
+                    INPUT.as_ptr() as *const c_char,
+                )
+            })?;
+            check(unsafe { ffi::udev_enumerate_scan_devices(enumerate) })?;
+
+            let mut paths = Vec::new();
+            let mut entry = unsafe { ffi::udev_enumerate_get_list_entry(enumerate) };
+            while !entry.is_null() {
+                let device = unsafe {
+                    ffi::udev_device_new_from_syspath(
+                        self.udev,
+                        ffi::udev_list_entry_get_name(entry),
+                    )
+                };
+                if !device.is_null() {
+                    paths.extend(unsafe { event_node(device) });
+                    unsafe { ffi::udev_device_unref(device) };
+                }
+                entry = unsafe { ffi::udev_list_entry_get_next(entry) };
+            }
+            Ok(paths)
+        })();
+
+        unsafe { ffi::udev_enumerate_unref(enumerate) };
+        result
+    }
+
+    /// Get the next device that was added or removed, or `None` if there
+    /// are no more events right now
+    pub fn next_event(&self) -> io::Result<Option<HotplugEvent>> {
+        loop {
+            let device = unsafe { ffi::udev_monitor_receive_device(self.monitor) };
+            if device.is_null() {
+                return Ok(None);
+            }
+
+            let event = unsafe {
+                let action = cstr(ffi::udev_device_get_action(device));
+                event_node(device).and_then(|path| match action {
+                    Some(b"add") => Some(HotplugEvent::Added(path)),
+                    Some(b"remove") => Some(HotplugEvent::Removed(path)),
+                    _ => None,
+                })
+            };
+            unsafe { ffi::udev_device_unref(device) };
+
+            if event.is_some() {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+impl AsRawFd for HotplugMonitor {
+    fn as_raw_fd(&self) -> RawFd {
+        unsafe { ffi::udev_monitor_get_fd(self.monitor) }
+    }
+}
+
+impl Drop for HotplugMonitor {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::udev_monitor_unref(self.monitor);
+            ffi::udev_unref(self.udev);
+        }
+    }
+}
+
+impl std::fmt::Debug for HotplugMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("HotplugMonitor")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+fn check(result: c_int) -> io::Result<()> {
+    match result {
+        error if error < 0 => Err(io::Error::from_raw_os_error(-error)),
+        _ => Ok(()),
+    }
+}
+
+unsafe fn cstr<'a>(ptr: *const c_char) -> Option<&'a [u8]> {
+    Some(CStr::from_ptr(ptr.as_ref()?).to_bytes())
+}
+
+/// The device node of the device if it is an event node
+unsafe fn event_node(device: *mut ffi::udev_device) -> Option<PathBuf> {
+    let devnode = cstr(ffi::udev_device_get_devnode(device))?;
+    if !devnode.starts_with(EVENT_NODE_PREFIX) {
+        return None;
+    }
+    Some(PathBuf::from(OsStr::from_bytes(devnode)))
+}
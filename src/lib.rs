@@ -42,18 +42,47 @@
 //! [dependencies]
 //! evdev-rs = { version = "0.4.0", features = ["serde"] }
 //! ```
+//!
+//! ## Hotplug
+//! to be notified about devices being added and removed with
+//! `HotplugMonitor`, you must enable the `udev` feature, which links to
+//! libudev through the `libudev-sys` crate.
+//!
+//! ## Async
+//! to read events as a `futures_core::Stream` with `Device::event_stream`,
+//! you must enable the `tokio` feature.
 
 #[macro_use]
 mod macros;
+mod bitmap;
+mod calibration;
+mod class;
+mod description;
 mod device;
 pub mod enums;
+mod ff;
+mod filter;
+mod frame;
+mod history;
+#[cfg(feature = "udev")]
+mod hotplug;
 pub mod logging;
+mod merge;
+#[cfg(feature = "serde")]
+mod remap;
+mod split;
+mod state;
+#[cfg(feature = "tokio")]
+mod stream;
+mod throttle;
+mod touch;
 mod uinput;
 pub mod util;
 
 use bitflags::bitflags;
 use libc::{c_uint, suseconds_t, time_t};
 use std::convert::{TryFrom, TryInto};
+use std::io;
 use std::time::{Duration, SystemTime, SystemTimeError, UNIX_EPOCH};
 
 use enums::*;
@@ -65,9 +94,21 @@ pub use util::InputPropIterator;
 
 use evdev_sys as raw;
 
+#[doc(inline)]
+pub use bitmap::CapabilityBitmap;
+#[doc(inline)]
+pub use calibration::Calibration;
+#[doc(inline)]
+pub use class::{DeviceClass, KeyboardLayout};
+#[doc(inline)]
+pub use description::{DescriptionDiff, DeviceDescription};
+#[doc(inline)]
+pub use device::BorrowedDevice;
 #[doc(inline)]
 pub use device::Device;
 #[doc(inline)]
+pub use device::DeviceSpec;
+#[doc(inline)]
 pub use device::DeviceWrapper;
 #[doc(inline)]
 pub use device::Enable;
@@ -76,11 +117,47 @@ pub use device::EnableCodeData;
 #[doc(inline)]
 pub use device::UninitDevice;
 #[doc(inline)]
-pub use uinput::UInputDevice;
+pub use ff::{FfEffect, FfEffectKind, FfEnvelope, FfReplay, FfTrigger};
+#[doc(inline)]
+pub use filter::{EventFilter, OnlyCode, OnlyType, SkipSyn};
+#[doc(inline)]
+pub use frame::{mt_contacts, Frames};
+#[doc(inline)]
+pub use history::ValueHistory;
+#[cfg(feature = "udev")]
+#[doc(inline)]
+pub use hotplug::{HotplugEvent, HotplugMonitor};
+#[doc(inline)]
+pub use merge::MergedReader;
+#[cfg(feature = "serde")]
+#[doc(inline)]
+pub use remap::RemapConfig;
+#[doc(inline)]
+pub use split::{DeviceControl, DeviceReader};
+#[doc(inline)]
+pub use state::DeviceState;
+#[cfg(feature = "tokio")]
+#[doc(inline)]
+pub use stream::EventStream;
+#[doc(inline)]
+pub use throttle::{ThrottlePolicy, ThrottledReader};
+#[doc(inline)]
+pub use touch::{MtExtents, MtMatrix, Touch, TouchFrame};
+#[doc(inline)]
+pub use uinput::{PartialWrite, UInputDevice};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// The clock used for the timestamps of a device's events
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockId {
+    /// The wall clock time, the kernel's default
+    Realtime = libc::CLOCK_REALTIME as isize,
+    /// The time since boot, not counting suspend, which never jumps
+    Monotonic = libc::CLOCK_MONOTONIC as isize,
+}
+
 pub enum GrabMode {
     /// Grab the device if not currently grabbed
     Grab = raw::LIBEVDEV_GRAB as isize,
@@ -89,6 +166,7 @@ pub enum GrabMode {
 }
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ReadFlag: u32 {
         /// Process data in sync mode
         const SYNC = 1;
@@ -102,7 +180,7 @@ bitflags! {
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ReadStatus {
     /// `next_event` has finished without an error and an event is available
     /// for processing.
@@ -113,6 +191,34 @@ pub enum ReadStatus {
     Sync = raw::LIBEVDEV_READ_STATUS_SYNC as isize,
 }
 
+/// Every outcome of reading an event, as returned by
+/// [Device::poll_event](crate::Device::poll_event)
+#[derive(Debug)]
+pub enum NextEvent {
+    /// An event was read in normal mode
+    Event(InputEvent),
+    /// libevdev received a SYN_DROPPED and the caller should now resync
+    /// the device, or an event of the state delta was read in sync mode
+    SyncEvent(InputEvent),
+    /// No event is available right now, or in sync mode, all events of the
+    /// state delta have been read
+    WouldBlock,
+    /// Reading failed, e.g. because the device was removed
+    Error(io::Error),
+}
+
+/// The flags and the outcome of a read, as returned by
+/// [Device::last_read_info](crate::Device::last_read_info)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadInfo {
+    /// the flags the read was done with
+    pub flags: ReadFlag,
+    /// the status of the read, `None` if it failed
+    pub status: Option<ReadStatus>,
+    /// the errno of a failed read, e.g. `EAGAIN` if no event was available
+    pub errno: Option<i32>,
+}
+
 pub enum LedState {
     /// Turn the LED on
     On = raw::LIBEVDEV_LED_ON as isize,
@@ -120,7 +226,54 @@ pub enum LedState {
     Off = raw::LIBEVDEV_LED_OFF as isize,
 }
 
-#[derive(Debug)]
+/// The direction of a scroll wheel
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScrollAxis {
+    /// REL_WHEEL and REL_WHEEL_HI_RES, positive values scroll up
+    Vertical,
+    /// REL_HWHEEL and REL_HWHEEL_HI_RES, positive values scroll right
+    Horizontal,
+}
+
+/// The amount scrolled by a wheel event, as returned by
+/// [InputEvent::scroll_delta]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollDelta {
+    pub axis: ScrollAxis,
+    /// the distance in detents of the wheel, fractional for
+    /// high-resolution events
+    pub clicks: f32,
+}
+
+/// The state of a key as given by the value of an EV_KEY event
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyState {
+    Released = 0,
+    Pressed = 1,
+    /// The key is held down and autorepeat triggered
+    Repeated = 2,
+}
+
+impl KeyState {
+    /// The key state of an EV_KEY event value, or `None` if the value is
+    /// not a valid key state
+    pub const fn from_value(value: i32) -> Option<KeyState> {
+        match value {
+            0 => Some(KeyState::Released),
+            1 => Some(KeyState::Pressed),
+            2 => Some(KeyState::Repeated),
+            _ => None,
+        }
+    }
+
+    /// The EV_KEY event value of this key state
+    pub const fn to_value(&self) -> i32 {
+        *self as i32
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize), derive(Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DeviceId {
     pub bustype: BusType,
     pub vendor: u16,
@@ -128,7 +281,41 @@ pub struct DeviceId {
     pub version: u16,
 }
 
-#[derive(Clone, Copy, Debug)]
+/// The version of the evdev protocol implemented by the kernel driver, as
+/// returned by EVIOCGVERSION
+///
+/// Versions compare by major, then minor, then patch number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EvdevVersion {
+    pub major: u16,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl EvdevVersion {
+    /// Decode a version packed as `major << 16 | minor << 8 | patch`
+    pub const fn from_raw(version: i32) -> EvdevVersion {
+        EvdevVersion {
+            major: (version >> 16) as u16,
+            minor: (version >> 8) as u8,
+            patch: version as u8,
+        }
+    }
+
+    /// The version packed as `major << 16 | minor << 8 | patch`
+    pub const fn to_raw(&self) -> i32 {
+        (self.major as i32) << 16 | (self.minor as i32) << 8 | self.patch as i32
+    }
+}
+
+impl std::fmt::Display for EvdevVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize), derive(Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 /// used by EVIOCGABS/EVIOCSABS ioctls
 pub struct AbsInfo {
     /// latest reported value for the axis
@@ -170,6 +357,136 @@ impl AbsInfo {
             resolution: self.resolution,
         }
     }
+
+    /// Add `delta` to `value`, wrapping around within the inclusive range
+    /// `[minimum, maximum]` of the axis, e.g. for rotary encoders.
+    ///
+    /// Adding 1 to `maximum` gives `minimum` and subtracting 1 from
+    /// `minimum` gives `maximum`. If `maximum` is less than `minimum`,
+    /// `value` is returned unchanged.
+    ///
+    /// ```rust
+    /// # use evdev_rs::AbsInfo;
+    /// let info = AbsInfo {
+    ///     value: 0,
+    ///     minimum: 0,
+    ///     maximum: 359,
+    ///     fuzz: 0,
+    ///     flat: 0,
+    ///     resolution: 0,
+    /// };
+    /// assert_eq!(info.wrapping_add(350, 20), 10);
+    /// ```
+    pub fn wrapping_add(&self, value: i32, delta: i32) -> i32 {
+        let minimum = self.minimum as i64;
+        let range = self.maximum as i64 - minimum + 1;
+        if range <= 0 {
+            return value;
+        }
+
+        let offset = (value as i64 - minimum + delta as i64).rem_euclid(range);
+        (minimum + offset) as i32
+    }
+
+    /// Returns `true` if `value` is within `flat` of the center of the axis.
+    ///
+    /// The center is the midpoint of `minimum` and `maximum`, so for an
+    /// axis ranging from 0 to 255 with a `flat` of 15, the values 112 to
+    /// 142 are within the deadzone. Joystick applications usually report
+    /// such values as the resting value.
+    pub fn in_deadzone(&self, value: i32) -> bool {
+        let center = (self.minimum as i64 + self.maximum as i64) / 2;
+        (value as i64 - center).abs() <= self.flat as i64
+    }
+
+    /// The axis in units of a different resolution, covering the same
+    /// physical range.
+    ///
+    /// `value`, `minimum`, `maximum`, `fuzz` and `flat` are multiplied by
+    /// `new_resolution / resolution` and rounded to the nearest integer.
+    /// If `resolution` or `new_resolution` is not positive, the physical
+    /// range is unknown and the axis is returned unchanged.
+    ///
+    /// ```rust
+    /// # use evdev_rs::AbsInfo;
+    /// let info = AbsInfo {
+    ///     value: 500,
+    ///     minimum: 0,
+    ///     maximum: 1000,
+    ///     fuzz: 4,
+    ///     flat: 0,
+    ///     resolution: 10,
+    /// };
+    /// let scaled = info.rescale(40);
+    /// assert_eq!((scaled.value, scaled.maximum, scaled.fuzz), (2000, 4000, 16));
+    /// ```
+    pub fn rescale(&self, new_resolution: i32) -> AbsInfo {
+        if self.resolution <= 0 || new_resolution <= 0 {
+            return *self;
+        }
+
+        let scale = |v: i32| {
+            saturate(scale_rounded(
+                v as i128,
+                new_resolution as i128,
+                self.resolution as i128,
+            ))
+        };
+        AbsInfo {
+            value: scale(self.value),
+            minimum: scale(self.minimum),
+            maximum: scale(self.maximum),
+            fuzz: scale(self.fuzz),
+            flat: scale(self.flat),
+            resolution: new_resolution,
+        }
+    }
+
+    /// Map `value` from the range of this axis to the range of `target`,
+    /// rounding to the nearest integer.
+    ///
+    /// `minimum` maps to `target.minimum` and `maximum` to `target.maximum`.
+    /// Values outside of the range are mapped the same way rather than
+    /// clamped. If this axis has an empty range, `target.minimum` is
+    /// returned.
+    ///
+    /// ```rust
+    /// # use evdev_rs::AbsInfo;
+    /// let info = |minimum, maximum| AbsInfo {
+    ///     value: 0,
+    ///     minimum,
+    ///     maximum,
+    ///     fuzz: 0,
+    ///     flat: 0,
+    ///     resolution: 0,
+    /// };
+    /// assert_eq!(info(0, 4095).scale_value(4095, &info(0, 1919)), 1919);
+    /// assert_eq!(info(-100, 100).scale_value(0, &info(0, 255)), 128);
+    /// ```
+    pub fn scale_value(&self, value: i32, target: &AbsInfo) -> i32 {
+        let range = self.maximum as i128 - self.minimum as i128;
+        if range == 0 {
+            return target.minimum;
+        }
+
+        let target_range = target.maximum as i128 - target.minimum as i128;
+        let offset = value as i128 - self.minimum as i128;
+        saturate(target.minimum as i128 + scale_rounded(offset, target_range, range))
+    }
+}
+
+/// `value * numerator / denominator` rounded to the nearest integer, with
+/// halves rounded up
+fn scale_rounded(value: i128, numerator: i128, denominator: i128) -> i128 {
+    let (numerator, denominator) = match denominator {
+        d if d < 0 => (-numerator, -d),
+        d => (numerator, d),
+    };
+    (2 * value * numerator + denominator).div_euclid(2 * denominator)
+}
+
+fn saturate(value: i128) -> i32 {
+    value.clamp(i32::MIN as i128, i32::MAX as i128) as i32
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize), derive(Deserialize))]
@@ -224,9 +541,37 @@ impl TimeVal {
             tv_usec: self.tv_usec,
         }
     }
+
+    /// The current time of CLOCK_MONOTONIC, e.g. to timestamp events for a
+    /// device that was switched to the monotonic clock with
+    /// [Device::set_clock_id](crate::Device::set_clock_id)
+    pub fn now_monotonic() -> io::Result<TimeVal> {
+        TimeVal::now(libc::CLOCK_MONOTONIC)
+    }
+
+    /// The current time of CLOCK_REALTIME, the clock the kernel uses for
+    /// event timestamps by default
+    pub fn now_realtime() -> io::Result<TimeVal> {
+        TimeVal::now(libc::CLOCK_REALTIME)
+    }
+
+    fn now(clock: libc::clockid_t) -> io::Result<TimeVal> {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+
+        match unsafe { libc::clock_gettime(clock, &mut ts) } {
+            0 => Ok(TimeVal::new(ts.tv_sec, (ts.tv_nsec / 1000) as suseconds_t)),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
 }
 
 /// The event structure itself
+///
+/// The `PartialEq`/`Eq` implementations compare all fields, including the
+/// timestamp. Use `same_event` to compare the type, code and value only.
 #[cfg_attr(feature = "serde", derive(Serialize), derive(Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct InputEvent {
@@ -245,6 +590,34 @@ impl InputEvent {
         }
     }
 
+    /// An EV_KEY event setting `key` to `state`, with a zero timestamp
+    pub const fn key(key: EV_KEY, state: KeyState) -> InputEvent {
+        InputEvent::zero_time(EventCode::EV_KEY(key), state.to_value())
+    }
+
+    /// An EV_REL event moving `axis` by `delta`, with a zero timestamp
+    pub const fn rel(axis: EV_REL, delta: i32) -> InputEvent {
+        InputEvent::zero_time(EventCode::EV_REL(axis), delta)
+    }
+
+    /// An EV_ABS event setting `axis` to `value`, with a zero timestamp
+    pub const fn abs(axis: EV_ABS, value: i32) -> InputEvent {
+        InputEvent::zero_time(EventCode::EV_ABS(axis), value)
+    }
+
+    /// A `SYN_REPORT` event, with a zero timestamp
+    pub const fn syn_report() -> InputEvent {
+        InputEvent::zero_time(EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0)
+    }
+
+    const fn zero_time(event_code: EventCode, value: i32) -> InputEvent {
+        InputEvent {
+            time: TimeVal::new(0, 0),
+            event_code,
+            value,
+        }
+    }
+
     pub fn event_type(&self) -> Option<EventType> {
         int_to_event_type(event_code_to_int(&self.event_code).0)
     }
@@ -269,6 +642,102 @@ impl InputEvent {
         }
     }
 
+    /// Parse an event from its kernel `struct input_event` representation,
+    /// e.g. from a buffer filled by a single `read(2)` on the device file.
+    ///
+    /// `bytes` must be exactly `size_of::<libc::input_event>()` bytes long,
+    /// which is 24 bytes on 64-bit and 16 bytes on most 32-bit platforms.
+    /// Returns `None` otherwise. The bytes don't need to be aligned.
+    ///
+    /// ```rust
+    /// # use evdev_rs::InputEvent;
+    /// # let buffer = InputEvent::new(
+    /// #     &evdev_rs::TimeVal::new(0, 0),
+    /// #     &evdev_rs::enums::EventCode::EV_SYN(evdev_rs::enums::EV_SYN::SYN_REPORT),
+    /// #     0,
+    /// # ).to_bytes();
+    /// let size = std::mem::size_of::<libc::input_event>();
+    /// let events: Vec<_> = buffer
+    ///     .chunks_exact(size)
+    ///     .filter_map(InputEvent::from_bytes)
+    ///     .collect();
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Option<InputEvent> {
+        if bytes.len() != std::mem::size_of::<libc::input_event>() {
+            return None;
+        }
+
+        let event = unsafe {
+            std::ptr::read_unaligned(bytes.as_ptr() as *const libc::input_event)
+        };
+        Some(InputEvent::from_raw(&event))
+    }
+
+    /// Convert the event to its kernel `struct input_event` representation,
+    /// e.g. to write it to a device file.
+    pub fn to_bytes(&self) -> [u8; std::mem::size_of::<libc::input_event>()] {
+        let event = self.as_raw();
+        unsafe { std::mem::transmute(event) }
+    }
+
+    /// Returns `true` if both events have the same type, code and value,
+    /// regardless of their timestamps.
+    ///
+    /// This is usually what tests and deduplication want, since two reads
+    /// of the same event never have the same timestamp. Use `==` to also
+    /// compare the timestamps.
+    pub fn same_event(&self, other: &InputEvent) -> bool {
+        self.event_code == other.event_code && self.value == other.value
+    }
+
+    /// The hardware timestamp in microseconds of an `MSC_TIMESTAMP` event,
+    /// or `None` for any other event.
+    ///
+    /// The timestamp is taken by the device itself and is independent of
+    /// the event time, which is when the kernel received the event. It has
+    /// no defined start and wraps around, only the differences between
+    /// timestamps are meaningful.
+    pub fn hw_timestamp_us(&self) -> Option<i32> {
+        match self.event_code {
+            EventCode::EV_MSC(EV_MSC::MSC_TIMESTAMP) => Some(self.value),
+            _ => None,
+        }
+    }
+
+    /// The amount scrolled by a REL_WHEEL, REL_HWHEEL, REL_WHEEL_HI_RES or
+    /// REL_HWHEEL_HI_RES event, or `None` for any other event.
+    ///
+    /// High-resolution events are in fractions of a detent, 120 being one
+    /// detent, and are converted to detents. A device with a
+    /// high-resolution wheel sends both kinds of events for the same
+    /// movement, so a consumer should only handle one of the two, see
+    /// [DeviceWrapper::has_hi_res_wheel].
+    ///
+    /// ```rust
+    /// use evdev_rs::enums::EV_REL;
+    /// use evdev_rs::{InputEvent, ScrollAxis};
+    ///
+    /// let delta = InputEvent::rel(EV_REL::REL_WHEEL_HI_RES, -60).scroll_delta().unwrap();
+    /// assert_eq!(delta.axis, ScrollAxis::Vertical);
+    /// assert_eq!(delta.clicks, -0.5);
+    /// ```
+    pub fn scroll_delta(&self) -> Option<ScrollDelta> {
+        let (axis, units_per_click) = match self.event_code {
+            EventCode::EV_REL(EV_REL::REL_WHEEL) => (ScrollAxis::Vertical, 1.0),
+            EventCode::EV_REL(EV_REL::REL_HWHEEL) => (ScrollAxis::Horizontal, 1.0),
+            EventCode::EV_REL(EV_REL::REL_WHEEL_HI_RES) => (ScrollAxis::Vertical, 120.0),
+            EventCode::EV_REL(EV_REL::REL_HWHEEL_HI_RES) => {
+                (ScrollAxis::Horizontal, 120.0)
+            }
+            _ => return None,
+        };
+
+        Some(ScrollDelta {
+            axis,
+            clicks: self.value as f32 / units_per_click,
+        })
+    }
+
     pub fn is_type(&self, ev_type: &EventType) -> bool {
         unsafe { raw::libevdev_event_is_type(&self.as_raw(), *ev_type as c_uint) == 1 }
     }
@@ -279,3 +748,26 @@ impl InputEvent {
         unsafe { raw::libevdev_event_is_code(&self.as_raw(), ev_type, ev_code) == 1 }
     }
 }
+
+/// Identifies the device an event was read from when the events of
+/// several devices are combined into one stream
+///
+/// [MergedReader] hands out a token for every device added to it, callers
+/// merging the events themselves can choose the value, e.g. the index of
+/// the device in their list of devices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeviceToken(pub usize);
+
+/// An event together with the device it was read from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TaggedEvent {
+    /// The device the event was read from
+    pub source: DeviceToken,
+    pub event: InputEvent,
+}
+
+impl TaggedEvent {
+    pub const fn new(source: DeviceToken, event: InputEvent) -> TaggedEvent {
+        TaggedEvent { source, event }
+    }
+}
@@ -1,4 +1,15 @@
 use evdev_sys as raw;
+use libc::{c_char, c_int, c_void, size_t};
+use std::ffi::CStr;
+
+extern "C" {
+    fn vsnprintf(
+        buf: *mut c_char,
+        size: size_t,
+        format: *const c_char,
+        args: raw::va_list,
+    ) -> c_int;
+}
 
 pub enum LogPriority {
     /// critical errors and application bugs
@@ -31,3 +42,30 @@ pub fn get_log_priority() -> LogPriority {
         }
     }
 }
+
+/// Device log handler forwarding libevdev's messages to the `log` crate
+pub(crate) extern "C" fn device_log_handler(
+    _dev: *const raw::libevdev,
+    priority: c_int,
+    _data: *mut c_void,
+    _file: *const c_char,
+    _line: c_int,
+    _func: *const c_char,
+    format: *const c_char,
+    args: raw::va_list,
+) {
+    // Longer messages are truncated, a va_list can only be consumed once
+    let mut buffer = [0 as c_char; 1024];
+    let result = unsafe { vsnprintf(buffer.as_mut_ptr(), buffer.len(), format, args) };
+    if result < 0 {
+        return;
+    }
+
+    let message = unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_string_lossy();
+    let level = match priority {
+        p if p <= raw::LIBEVDEV_LOG_ERROR => log::Level::Error,
+        p if p <= raw::LIBEVDEV_LOG_INFO => log::Level::Info,
+        _ => log::Level::Debug,
+    };
+    log::log!(level, "{}", message.trim_end());
+}
@@ -2,7 +2,7 @@ macro_rules! string_getter {
     ( $( #[$doc:meta], $func_name:ident, $c_func: ident ),* ) => {
         $(
             #[$doc]
-            fn $func_name (&self) -> Option<&str> {
+            fn $func_name (&self) -> Option<&'_ str> {
                 unsafe {
                     ptr_to_str(raw::$c_func(self.raw()))
                 }
@@ -14,7 +14,7 @@ macro_rules! string_getter {
 macro_rules! string_setter {
     ( $( $func_name:ident, $c_func: ident ),* ) => {
         $(
-            fn $func_name (&self, field: &str) {
+            fn $func_name (&mut self, field: &str) {
                 let field = CString::new(field).unwrap();
                 unsafe {
                     raw::$c_func(self.raw(), field.as_ptr())
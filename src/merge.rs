@@ -0,0 +1,139 @@
+use crate::{Device, DeviceToken, TaggedEvent};
+use libc::c_int;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::time::{Duration, Instant};
+
+/// Reads the events of several devices as one stream, tagging every event
+/// with the device it was read from
+///
+/// The devices are read round-robin so a busy device can't starve the
+/// others, and `poll` is used to wait for any of them to become readable.
+/// After a `SYN_DROPPED` of a device, the events of its sync delta are
+/// returned like regular events.
+///
+/// ```rust,no_run
+/// use evdev_rs::{Device, MergedReader};
+/// use std::time::Duration;
+///
+/// let mut reader = MergedReader::new();
+/// let keyboard = reader.add(Device::new_from_path("/dev/input/event0").unwrap());
+/// let mouse = reader.add(Device::new_from_path("/dev/input/event1").unwrap());
+/// while let Some(ev) = reader.next_event(Some(Duration::from_secs(1))).unwrap() {
+///     if ev.source == keyboard {
+///         println!("keyboard: {:?}", ev.event);
+///     } else if ev.source == mouse {
+///         println!("mouse: {:?}", ev.event);
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MergedReader {
+    devices: Vec<(DeviceToken, Device)>,
+    next_token: usize,
+    next_device: usize,
+    failed: Option<DeviceToken>,
+}
+
+impl MergedReader {
+    pub fn new() -> MergedReader {
+        MergedReader::default()
+    }
+
+    /// Add a device, returning the token its events are tagged with
+    pub fn add(&mut self, device: Device) -> DeviceToken {
+        let token = DeviceToken(self.next_token);
+        self.next_token += 1;
+        self.devices.push((token, device));
+        token
+    }
+
+    /// Remove a device, e.g. after reading it failed, returning it
+    pub fn remove(&mut self, token: DeviceToken) -> Option<Device> {
+        let index = self.devices.iter().position(|(t, _)| *t == token)?;
+        Some(self.devices.remove(index).1)
+    }
+
+    /// The device added with the given token
+    pub fn device(&self, token: DeviceToken) -> Option<&Device> {
+        self.devices
+            .iter()
+            .find(|(t, _)| *t == token)
+            .map(|(_, device)| device)
+    }
+
+    /// The device whose read failed with the last error `next_event`
+    /// returned, `None` if the error came from `poll`
+    pub fn failed(&self) -> Option<DeviceToken> {
+        self.failed
+    }
+
+    /// Get the next event of any of the devices, waiting at most `timeout`
+    /// for one, or indefinitely if `timeout` is `None`
+    ///
+    /// Returns `Ok(None)` if no event was available before the timeout
+    /// expired, or if there are no devices. If reading a device fails, the
+    /// error is returned and `failed` returns the token of the device.
+    pub fn next_event(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<TaggedEvent>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self.failed = None;
+
+        loop {
+            if self.devices.is_empty() {
+                return Ok(None);
+            }
+
+            let count = self.devices.len();
+            for offset in 0..count {
+                let index = (self.next_device + offset) % count;
+                let (token, device) = &self.devices[index];
+                match device.next_event_or_delta() {
+                    Ok(Some(event)) => {
+                        self.next_device = index + 1;
+                        return Ok(Some(TaggedEvent::new(*token, event)));
+                    }
+                    Ok(None) => (),
+                    Err(error) => {
+                        self.failed = Some(*token);
+                        return Err(error);
+                    }
+                }
+            }
+
+            let ms = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    // Round up so we never wake up before the deadline
+                    let ms = remaining.as_nanos().div_ceil(1_000_000);
+                    ms.min(c_int::MAX as u128) as c_int
+                }
+                None => -1,
+            };
+            let mut pollfds: Vec<_> = self
+                .devices
+                .iter()
+                .map(|(_, device)| libc::pollfd {
+                    fd: device.file().as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                })
+                .collect();
+            let result = unsafe {
+                libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, ms)
+            };
+            match result {
+                0 => return Ok(None),
+                -1 => return Err(io::Error::last_os_error()),
+                _ => (),
+            }
+        }
+    }
+
+    /// Returns the devices along with their tokens
+    pub fn into_inner(self) -> Vec<(DeviceToken, Device)> {
+        self.devices
+    }
+}
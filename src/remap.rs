@@ -0,0 +1,127 @@
+use crate::enums::EventCode;
+use crate::InputEvent;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A mapping of event codes to the codes they should be replaced with,
+/// e.g. loaded from a config file
+///
+/// It is (de)serialized as a map of event code names, e.g. in JSON
+/// `{ "KEY_CAPSLOCK": "KEY_LEFTCTRL" }`, with the names parsed by
+/// [EventCode::from_name]. Only available with the `serde` feature.
+///
+/// ```rust
+/// use evdev_rs::enums::{EventCode, EV_KEY};
+/// use evdev_rs::{InputEvent, KeyState, RemapConfig};
+///
+/// let mut config = RemapConfig::default();
+/// config.insert(
+///     EventCode::EV_KEY(EV_KEY::KEY_CAPSLOCK),
+///     EventCode::EV_KEY(EV_KEY::KEY_LEFTCTRL),
+/// );
+///
+/// let ev = InputEvent::key(EV_KEY::KEY_CAPSLOCK, KeyState::Pressed);
+/// assert_eq!(
+///     config.remap(&ev).event_code,
+///     EventCode::EV_KEY(EV_KEY::KEY_LEFTCTRL)
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RemapConfig {
+    map: HashMap<EventCode, EventCode>,
+}
+
+impl RemapConfig {
+    /// Replace events with the code `from` with events with the code `to`,
+    /// returning the code `from` was mapped to before
+    pub fn insert(&mut self, from: EventCode, to: EventCode) -> Option<EventCode> {
+        self.map.insert(from, to)
+    }
+
+    /// The code events with the code `from` are replaced with, if any
+    pub fn get(&self, from: &EventCode) -> Option<EventCode> {
+        self.map.get(from).copied()
+    }
+
+    /// The event with its code replaced if the code is mapped, the value and
+    /// the time are kept
+    pub fn remap(&self, event: &InputEvent) -> InputEvent {
+        match self.get(&event.event_code) {
+            Some(code) => InputEvent::new(&event.time, &code, event.value),
+            None => event.clone(),
+        }
+    }
+
+    /// The mapping of event codes
+    pub fn map(&self) -> &HashMap<EventCode, EventCode> {
+        &self.map
+    }
+
+    pub fn into_map(self) -> HashMap<EventCode, EventCode> {
+        self.map
+    }
+}
+
+impl From<HashMap<EventCode, EventCode>> for RemapConfig {
+    fn from(map: HashMap<EventCode, EventCode>) -> RemapConfig {
+        RemapConfig { map }
+    }
+}
+
+impl Serialize for RemapConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Sorted, so a config written back to a file doesn't change order
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort();
+
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (from, to) in entries {
+            map.serialize_entry(&code_name::<S>(from)?, &code_name::<S>(to)?)?;
+        }
+        map.end()
+    }
+}
+
+fn code_name<S: Serializer>(code: &EventCode) -> Result<String, S::Error> {
+    match code.to_string() {
+        name if name.is_empty() => Err(ser::Error::custom(format!(
+            "event code {:?} has no name",
+            code
+        ))),
+        name => Ok(name),
+    }
+}
+
+impl<'de> Deserialize<'de> for RemapConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(RemapConfigVisitor)
+    }
+}
+
+struct RemapConfigVisitor;
+
+impl<'de> Visitor<'de> for RemapConfigVisitor {
+    type Value = RemapConfig;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of event code names")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut access: A,
+    ) -> Result<RemapConfig, A::Error> {
+        let mut config = RemapConfig::default();
+        while let Some((from, to)) = access.next_entry::<String, String>()? {
+            config.insert(parse_code::<A::Error>(&from)?, parse_code::<A::Error>(&to)?);
+        }
+        Ok(config)
+    }
+}
+
+fn parse_code<E: de::Error>(name: &str) -> Result<EventCode, E> {
+    EventCode::from_name(name)
+        .ok_or_else(|| E::custom(format!("unknown event code {:?}", name)))
+}
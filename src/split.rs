@@ -0,0 +1,144 @@
+use crate::ff;
+use crate::{Device, FfEffect, GrabMode};
+use libc::c_int;
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::sync::Mutex;
+
+use evdev_sys as raw;
+
+/// The read half of a device split with [Device::split]
+///
+/// It owns the libevdev context of the device, so everything that reads
+/// events or the state libevdev keeps about the device goes through it.
+/// It derefs to the [Device] for that, but doesn't give out a `&mut
+/// Device`, so the device can't be grabbed or have its file changed
+/// through the reader, use the [DeviceControl] for grabbing.
+#[derive(Debug)]
+pub struct DeviceReader {
+    device: Device,
+}
+
+impl DeviceReader {
+    pub(crate) fn new(device: Device) -> DeviceReader {
+        DeviceReader { device }
+    }
+
+    /// Returns the device along with the grab of `control`, the control
+    /// the device was split into
+    ///
+    /// The device is grabbed if and only if `control` grabbed it, so
+    /// `Device::grab` and `close` work as if the device had never been
+    /// split. A grab held by `control` is handed over to libevdev, which
+    /// fails if another client grabs the device in between.
+    pub fn into_inner(self, control: DeviceControl) -> io::Result<Device> {
+        let mut device = self.device;
+        device.sync_grab(&control)?;
+        Ok(device)
+    }
+}
+
+impl Deref for DeviceReader {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// The control half of a device split with [Device::split]
+///
+/// It grabs the device and manages its force feedback effects through
+/// its own duplicate of the device file descriptor, without touching the
+/// libevdev context of the [DeviceReader]. It is `Send` and `Sync`, so it
+/// can be shared between threads, e.g. in an `Arc`.
+#[derive(Debug)]
+pub struct DeviceControl {
+    file: File,
+    grabbed: Mutex<bool>,
+    has_autocenter: bool,
+}
+
+impl DeviceControl {
+    pub(crate) fn new(file: File, grabbed: bool, has_autocenter: bool) -> DeviceControl {
+        DeviceControl {
+            file,
+            grabbed: Mutex::new(grabbed),
+            has_autocenter,
+        }
+    }
+
+    /// Grab or ungrab the device through a kernel EVIOCGRAB
+    ///
+    /// As with `Device::grab`, grabbing a grabbed device or ungrabbing an
+    /// ungrabbed device is a noop. The grab applies to the open file shared
+    /// with the reader, so while the device is grabbed the reader still
+    /// gets all events and other clients get none. Unlike `Device::grab`
+    /// the held down keys are not recorded, `pressed_keys_at_grab` of the
+    /// reader stays as it was when the device was split.
+    pub fn grab(&self, grab: GrabMode) -> io::Result<()> {
+        let mut grabbed = self.grabbed.lock().unwrap_or_else(|e| e.into_inner());
+        let want = matches!(grab, GrabMode::Grab);
+        if *grabbed == want {
+            return Ok(());
+        }
+
+        let result = unsafe {
+            libc::ioctl(self.file.as_raw_fd(), raw::EVIOCGRAB as _, want as c_int)
+        };
+
+        match result {
+            -1 => Err(io::Error::last_os_error()),
+            _ => {
+                *grabbed = want;
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether the device is grabbed through this control, or was grabbed
+    /// when it was split
+    pub fn is_grabbed(&self) -> bool {
+        *self.grabbed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Upload a force feedback effect, see `Device::upload_ff_effect`
+    pub fn upload_ff_effect(&self, effect: &FfEffect) -> io::Result<i16> {
+        ff::upload(&self.file, effect)
+    }
+
+    /// Remove an uploaded force feedback effect, see
+    /// `Device::erase_ff_effect`
+    pub fn erase_ff_effect(&self, id: i16) -> io::Result<()> {
+        ff::erase(&self.file, id)
+    }
+
+    /// Play or stop an uploaded force feedback effect, see
+    /// `Device::play_ff_effect`
+    pub fn play_ff_effect(&self, id: i16, count: i32) -> io::Result<()> {
+        ff::play(&self.file, id, count)
+    }
+
+    /// Set the autocenter strength, see `Device::set_autocenter`
+    pub fn set_autocenter(&self, strength: u16) -> io::Result<()> {
+        if !self.has_autocenter {
+            return Err(io::Error::from_raw_os_error(libc::ENOTSUP));
+        }
+
+        ff::set_autocenter(&self.file, strength)
+    }
+}
+
+impl AsRawFd for DeviceControl {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl AsFd for DeviceControl {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
@@ -0,0 +1,165 @@
+use crate::device::DeviceWrapper;
+use crate::enums::{EventCode, EventType, EV_ABS};
+use crate::util::{is_mt_code, EventCodeIterator};
+use crate::{InputEvent, TimeVal};
+use std::collections::BTreeMap;
+
+/// The event types for which libevdev keeps track of the current value
+const STATEFUL_TYPES: [EventType; 4] = [
+    EventType::EV_KEY,
+    EventType::EV_ABS,
+    EventType::EV_LED,
+    EventType::EV_SW,
+];
+
+/// A snapshot of the current values of a device
+///
+/// Holds the value of every supported EV_KEY, EV_ABS, EV_LED and EV_SW
+/// code, and for multitouch devices the value of every ABS_MT_* code in
+/// every slot. The snapshot is a copy, it does not change as events are
+/// read from the device.
+///
+/// ```rust
+/// use evdev_rs::enums::{EventCode, EV_KEY};
+/// use evdev_rs::{DeviceState, DeviceWrapper, UninitDevice};
+///
+/// let dev = UninitDevice::new().unwrap();
+/// let code = EventCode::EV_KEY(EV_KEY::KEY_A);
+/// dev.enable(code).unwrap();
+/// dev.set_event_value(&code, 1).unwrap();
+///
+/// let state = DeviceState::from_device(&dev);
+/// assert_eq!(state.value(&code), Some(1));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceState {
+    values: BTreeMap<EventCode, i32>,
+    slots: Vec<BTreeMap<EventCode, i32>>,
+}
+
+impl DeviceState {
+    /// Create an empty state
+    pub fn new() -> DeviceState {
+        DeviceState::default()
+    }
+
+    /// Take a snapshot of the current values of the given device
+    pub fn from_device<D: DeviceWrapper>(device: &D) -> DeviceState {
+        let mut state = DeviceState::new();
+
+        for ev_type in STATEFUL_TYPES.iter() {
+            if !device.has_event_type(ev_type) {
+                continue;
+            }
+
+            for code in EventCodeIterator::new(ev_type) {
+                if let Some(value) = device.event_value(&code) {
+                    state.values.insert(code, value);
+                }
+            }
+        }
+
+        for slot in device.slot_range().unwrap_or(0..0) {
+            let mut values = BTreeMap::new();
+            for code in EventCodeIterator::new(&EventType::EV_ABS) {
+                if !is_mt_code(&code) {
+                    continue;
+                }
+                if let Some(value) = device.slot_value(slot, &code) {
+                    values.insert(code, value);
+                }
+            }
+            state.slots.push(values);
+        }
+
+        state
+    }
+
+    /// The value of the given code, or `None` if the code is not part of
+    /// this state
+    pub fn value(&self, code: &EventCode) -> Option<i32> {
+        self.values.get(code).copied()
+    }
+
+    /// Set the value of the given code
+    pub fn set_value(&mut self, code: &EventCode, value: i32) {
+        self.values.insert(*code, value);
+    }
+
+    /// The value of the given ABS_MT_* code in the given slot, or `None`
+    /// if the slot or code is not part of this state
+    pub fn slot_value(&self, slot: u32, code: &EventCode) -> Option<i32> {
+        self.slots.get(slot as usize)?.get(code).copied()
+    }
+
+    /// The number of slots in this state, 0 for devices without slots
+    pub fn num_slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Iterate over all codes and their values, in the order of the codes
+    pub fn values(&self) -> impl Iterator<Item = (&EventCode, &i32)> {
+        self.values.iter()
+    }
+
+    /// The events that turn this state into `current`, followed by a
+    /// `SYN_REPORT`, or no events at all if the states are the same.
+    ///
+    /// An event is returned for every code of `current` whose value differs
+    /// from this state. For devices with slots, the changed ABS_MT_* values
+    /// are preceded by an ABS_MT_SLOT event selecting their slot, so the
+    /// events can be applied in order like a sync delta. The events have a
+    /// zero timestamp.
+    pub fn changes(&self, current: &DeviceState) -> Vec<InputEvent> {
+        let time = TimeVal::new(0, 0);
+        let slot = EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT);
+        // The ABS_MT_* values of a device with slots are those of the
+        // current slot, which are part of the slot values
+        let per_slot = |code: &EventCode| {
+            current.num_slots() > 0 && (*code == slot || is_mt_code(code))
+        };
+
+        let mut events: Vec<InputEvent> = current
+            .values()
+            .filter(|(code, value)| !per_slot(code) && self.value(code) != Some(**value))
+            .map(|(code, value)| InputEvent::new(&time, code, *value))
+            .collect();
+
+        let mut selected = self.value(&slot);
+        for index in 0..current.num_slots() as u32 {
+            let mut changed = current
+                .slot_values(index)
+                .filter(|(code, value)| self.slot_value(index, code) != Some(**value))
+                .peekable();
+            if changed.peek().is_none() {
+                continue;
+            }
+
+            if selected != Some(index as i32) {
+                selected = Some(index as i32);
+                events.push(InputEvent::abs(EV_ABS::ABS_MT_SLOT, index as i32));
+            }
+            events.extend(
+                changed.map(|(code, value)| InputEvent::new(&time, code, *value)),
+            );
+        }
+        match current.value(&slot) {
+            Some(value) if selected != Some(value) => {
+                events.push(InputEvent::abs(EV_ABS::ABS_MT_SLOT, value))
+            }
+            _ => (),
+        }
+
+        if !events.is_empty() {
+            events.push(InputEvent::syn_report());
+        }
+        events
+    }
+
+    /// Iterate over the ABS_MT_* codes and their values in the given slot,
+    /// in the order of the codes. Empty if the slot is not part of this
+    /// state.
+    pub fn slot_values(&self, slot: u32) -> impl Iterator<Item = (&EventCode, &i32)> {
+        self.slots.get(slot as usize).into_iter().flatten()
+    }
+}
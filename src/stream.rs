@@ -0,0 +1,107 @@
+//! Reading the events of a device asynchronously with tokio. Only
+//! available with the `tokio` feature.
+
+use crate::{Device, InputEvent};
+use futures_core::Stream;
+use std::future::poll_fn;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::unix::AsyncFd;
+
+/// A stream of the events of a device, created by [Device::event_stream]
+///
+/// The stream waits for the device file to become readable through a tokio
+/// `AsyncFd`, so it must be created and polled within a tokio runtime.
+/// After a `SYN_DROPPED` the events of the sync delta are yielded like
+/// regular events. Errors are yielded as they occur, the stream ends after
+/// the device was removed (`ENODEV`).
+pub struct EventStream<'a> {
+    device: &'a Device,
+    fd: AsyncFd<RawFd>,
+    removed: bool,
+}
+
+impl Device {
+    /// Get a stream of the events of the device, to use it with stream
+    /// combinators or `select!`
+    ///
+    /// The device file must have been opened with `O_NONBLOCK`, as
+    /// `new_from_path` does. Fails if the file can't be registered with the
+    /// tokio reactor.
+    ///
+    /// ```rust,no_run
+    /// use evdev_rs::Device;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let d = Device::new_from_path("/dev/input/event0")?;
+    /// let mut events = d.event_stream()?;
+    /// while let Some(ev) = events.next_event().await {
+    ///     println!("{:?}", ev?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn event_stream(&self) -> io::Result<EventStream<'_>> {
+        Ok(EventStream {
+            device: self,
+            fd: AsyncFd::new(self.file().as_raw_fd())?,
+            removed: false,
+        })
+    }
+}
+
+impl EventStream<'_> {
+    /// Wait for the next event, `None` once the device was removed
+    pub async fn next_event(&mut self) -> Option<io::Result<InputEvent>> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Returns the device the events are read from
+    pub fn device(&self) -> &Device {
+        self.device
+    }
+}
+
+impl Stream for EventStream<'_> {
+    type Item = io::Result<InputEvent>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<io::Result<InputEvent>>> {
+        let this = self.get_mut();
+        if this.removed {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            // libevdev may hold events it read already, which don't make
+            // the file readable again, so read before waiting
+            match this.device.next_event_or_delta() {
+                Ok(Some(event)) => return Poll::Ready(Some(Ok(event))),
+                Ok(None) => (),
+                Err(error) => {
+                    this.removed = error.raw_os_error() == Some(libc::ENODEV);
+                    return Poll::Ready(Some(Err(error)));
+                }
+            }
+
+            match this.fd.poll_read_ready(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(error)) => return Poll::Ready(Some(Err(error))),
+                // The file was read until EAGAIN above, wait for new events
+                Poll::Ready(Ok(mut guard)) => guard.clear_ready(),
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for EventStream<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("EventStream")
+            .field("device", &self.device)
+            .finish()
+    }
+}
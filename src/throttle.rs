@@ -0,0 +1,151 @@
+use crate::enums::{EventCode, EventType, EV_SYN};
+use crate::{Device, InputEvent, ReadFlag};
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// What a [ThrottledReader] does with events beyond its limit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThrottlePolicy {
+    /// Discard the events
+    Drop,
+    /// Merge the events into one event per code, which are returned
+    /// followed by a `SYN_REPORT` once the limit allows it again, counting
+    /// toward the limit like any other event. EV_REL deltas are summed up,
+    /// for all other types the last value is kept.
+    Coalesce,
+}
+
+/// Reads events from a device, but at most a given number per second
+///
+/// This protects a consumer from event storms, e.g. a broken sensor or a
+/// stuck key flooding the device with events. The events are read in
+/// normal mode without blocking, a `SYN_DROPPED` is passed on like any
+/// other event.
+///
+/// ```rust,no_run
+/// use evdev_rs::{Device, ThrottlePolicy, ThrottledReader};
+///
+/// let d = Device::new_from_path("/dev/input/event0").unwrap();
+/// let mut reader = ThrottledReader::new(d, 1000, ThrottlePolicy::Coalesce);
+/// while let Some(ev) = reader.next_event().unwrap() {
+///     println!("{:?}", ev);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ThrottledReader {
+    device: Device,
+    max_per_second: u32,
+    policy: ThrottlePolicy,
+    window_start: Instant,
+    count: u32,
+    coalesced: Vec<InputEvent>,
+    ready: VecDeque<InputEvent>,
+    dropped: u64,
+}
+
+impl ThrottledReader {
+    /// Wrap `device`, returning at most `max_per_second` events per second
+    pub fn new(
+        device: Device,
+        max_per_second: u32,
+        policy: ThrottlePolicy,
+    ) -> ThrottledReader {
+        ThrottledReader {
+            device,
+            max_per_second,
+            policy,
+            window_start: Instant::now(),
+            count: 0,
+            coalesced: Vec::new(),
+            ready: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Get the next event
+    ///
+    /// Returns `Ok(None)` if no event is available right now, including
+    /// when all available events were over the limit and have been dropped
+    /// or coalesced.
+    pub fn next_event(&mut self) -> io::Result<Option<InputEvent>> {
+        loop {
+            if self.window_start.elapsed() >= Duration::from_secs(1) {
+                self.window_start = Instant::now();
+                self.count = 0;
+                self.flush_coalesced();
+            }
+
+            if self.count < self.max_per_second {
+                if let Some(ev) = self.ready.pop_front() {
+                    self.count += 1;
+                    return Ok(Some(ev));
+                }
+            }
+
+            let ev = match self.device.try_next_event(ReadFlag::NORMAL)? {
+                Some((_, ev)) => ev,
+                None => return Ok(None),
+            };
+
+            if self.count < self.max_per_second {
+                self.count += 1;
+                return Ok(Some(ev));
+            }
+
+            self.dropped += 1;
+            if self.policy == ThrottlePolicy::Coalesce {
+                self.coalesce(ev);
+            }
+        }
+    }
+
+    /// The number of events over the limit so far, whether they were
+    /// dropped or coalesced
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Returns the wrapped device
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the wrapped device, discarding any coalesced events
+    pub fn into_inner(self) -> Device {
+        self.device
+    }
+
+    fn coalesce(&mut self, ev: InputEvent) {
+        if ev.is_type(&EventType::EV_SYN) {
+            return;
+        }
+
+        match self
+            .coalesced
+            .iter_mut()
+            .find(|pending| pending.event_code == ev.event_code)
+        {
+            Some(pending) if ev.is_type(&EventType::EV_REL) => {
+                pending.value = pending.value.saturating_add(ev.value);
+                pending.time = ev.time;
+            }
+            Some(pending) => *pending = ev,
+            None => self.coalesced.push(ev),
+        }
+    }
+
+    fn flush_coalesced(&mut self) {
+        let last = match self.coalesced.iter().map(|ev| ev.time).max() {
+            Some(time) => time,
+            None => return,
+        };
+
+        self.ready.extend(self.coalesced.drain(..));
+        self.ready.push_back(InputEvent::new(
+            &last,
+            &EventCode::EV_SYN(EV_SYN::SYN_REPORT),
+            0,
+        ));
+    }
+}
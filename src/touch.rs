@@ -0,0 +1,102 @@
+use crate::enums::EV_ABS;
+
+/// A single contact of a multitouch device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Touch {
+    /// the slot the contact is tracked in
+    pub slot: u32,
+    /// the ABS_MT_TRACKING_ID of the contact
+    pub id: i32,
+    /// the ABS_MT_POSITION_X of the contact
+    pub x: i32,
+    /// the ABS_MT_POSITION_Y of the contact
+    pub y: i32,
+    /// the ABS_MT_PRESSURE of the contact, if supported by the device
+    pub pressure: Option<i32>,
+    /// the ABS_MT_TOUCH_MAJOR of the contact, if supported by the device
+    pub major: Option<i32>,
+    /// the ABS_MT_TOUCH_MINOR of the contact, if supported by the device
+    pub minor: Option<i32>,
+}
+
+/// The active contacts of a multitouch device at one point in time
+///
+/// Returned by [DeviceWrapper::touch_frame](crate::DeviceWrapper::touch_frame).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TouchFrame {
+    /// the active contacts, ordered by slot
+    pub touches: Vec<Touch>,
+}
+
+/// The coordinate space of the touches of a device
+///
+/// Returned by [DeviceWrapper::mt_extents](crate::DeviceWrapper::mt_extents).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MtExtents {
+    pub x_min: i32,
+    pub x_max: i32,
+    /// the resolution of the x axis in units per mm, 0 if unknown
+    pub x_resolution: i32,
+    pub y_min: i32,
+    pub y_max: i32,
+    /// the resolution of the y axis in units per mm, 0 if unknown
+    pub y_resolution: i32,
+}
+
+/// The values of all supported ABS_MT_* codes in all slots of a multitouch
+/// device, one row per slot and one column per code
+///
+/// Returned by [DeviceWrapper::mt_matrix](crate::DeviceWrapper::mt_matrix).
+/// The values are stored row by row in one buffer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MtMatrix {
+    num_slots: usize,
+    codes: Vec<EV_ABS>,
+    values: Vec<i32>,
+}
+
+impl MtMatrix {
+    pub(crate) fn new(
+        num_slots: usize,
+        codes: Vec<EV_ABS>,
+        values: Vec<i32>,
+    ) -> MtMatrix {
+        MtMatrix {
+            num_slots,
+            codes,
+            values,
+        }
+    }
+
+    /// The ABS_MT_* codes of the columns, in ascending order
+    pub fn codes(&self) -> &[EV_ABS] {
+        &self.codes
+    }
+
+    /// The number of rows, i.e. the number of slots
+    pub fn num_slots(&self) -> usize {
+        self.num_slots
+    }
+
+    /// The values of all codes in `slot`, in the order of `codes`
+    pub fn row(&self, slot: u32) -> Option<&[i32]> {
+        if slot as usize >= self.num_slots {
+            return None;
+        }
+        let len = self.codes.len();
+        let start = slot as usize * len;
+        self.values.get(start..start + len)
+    }
+
+    /// The value of `code` in `slot`, or `None` if the code is not a
+    /// column or the slot is out of range
+    pub fn value(&self, slot: u32, code: EV_ABS) -> Option<i32> {
+        let column = self.codes.iter().position(|c| *c == code)?;
+        self.row(slot).map(|row| row[column])
+    }
+
+    /// All values, row by row
+    pub fn as_slice(&self) -> &[i32] {
+        &self.values
+    }
+}
@@ -1,8 +1,15 @@
-use crate::{device::DeviceWrapper, InputEvent};
+use crate::{
+    device::DeviceWrapper, AbsInfo, DeviceId, DeviceState, InputEvent, KeyState,
+    UninitDevice,
+};
 use libc::c_int;
+use std::fmt;
 use std::io;
 use std::os::unix::io::RawFd;
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::enums::*;
 use crate::util::*;
 
 use evdev_sys as raw;
@@ -10,6 +17,7 @@ use evdev_sys as raw;
 /// Opaque struct representing an evdev uinput device
 pub struct UInputDevice {
     raw: *mut raw::libevdev_uinput,
+    hi_res_wheel: bool,
 }
 
 unsafe impl Send for UInputDevice {}
@@ -23,7 +31,24 @@ impl UInputDevice {
     ///
     /// The uinput device will be an exact copy of the libevdev device, minus
     /// the bits that uinput doesn't allow to be set.
+    ///
+    /// The name, ids and phys set on `device` are used for the uinput
+    /// device, so `set_phys` can be used to give a virtual device a stable
+    /// phys string to identify it by. uinput doesn't allow setting the
+    /// uniq, the uniq of the created device is always empty.
+    ///
+    /// Returns an error with `InvalidInput` if `device` has no event type
+    /// other than EV_SYN enabled, which the kernel would reject with a bare
+    /// `EINVAL`, see `is_empty`.
     pub fn create_from_device<T: DeviceWrapper>(device: &T) -> io::Result<UInputDevice> {
+        if device.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the device supports no event types but EV_SYN, enable at least \
+                 one event code",
+            ));
+        }
+
         let mut libevdev_uinput = std::ptr::null_mut();
         let result = unsafe {
             raw::libevdev_uinput_create_from_device(
@@ -36,11 +61,67 @@ impl UInputDevice {
         match result {
             0 => Ok(UInputDevice {
                 raw: libevdev_uinput,
+                hi_res_wheel: device.has_hi_res_wheel(),
             }),
             error => Err(io::Error::from_raw_os_error(-error)),
         }
     }
 
+    /// Create a uinput device copying `device` like `create_from_device`,
+    /// but with the given id and name instead of those of `device`.
+    ///
+    /// This allows creating several virtual devices from one source device
+    /// which consumers can still tell apart, e.g. when splitting one
+    /// physical device into several virtual ones.
+    pub fn create_from_device_with_id<T: DeviceWrapper>(
+        device: &T,
+        id: &DeviceId,
+        name: &str,
+    ) -> io::Result<UInputDevice> {
+        let mut clone = UninitDevice::clone_from_filtered(device, |_| true)?;
+
+        clone.set_name(name);
+        clone.set_bustype(id.bustype as u16);
+        clone.set_vendor_id(id.vendor);
+        clone.set_product_id(id.product);
+        clone.set_version(id.version);
+
+        UInputDevice::create_from_device(&clone)
+    }
+
+    /// Create a virtual keyboard supporting all the standard keyboard keys
+    /// (`KEY_ESC` up to, but not including, the first `BTN_*` code).
+    pub fn virtual_keyboard() -> io::Result<UInputDevice> {
+        let device = new_virtual_device("evdev-rs virtual keyboard")?;
+
+        for code in EventCodeIterator::new(&EventType::EV_KEY) {
+            match code {
+                EventCode::EV_KEY(EV_KEY::KEY_RESERVED) => continue,
+                EventCode::EV_KEY(key) if key >= EV_KEY::BTN_0 => break,
+                _ => device.enable(code)?,
+            }
+        }
+
+        UInputDevice::create_from_device(&device)
+    }
+
+    /// Create a virtual mouse with relative X/Y axes, a scroll wheel and left
+    /// and right buttons.
+    ///
+    /// The buttons are required for the device to be detected as a mouse,
+    /// a device with only relative axes is ignored by most consumers.
+    pub fn virtual_mouse() -> io::Result<UInputDevice> {
+        let device = new_virtual_device("evdev-rs virtual mouse")?;
+
+        device.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT))?;
+        device.enable(EventCode::EV_KEY(EV_KEY::BTN_RIGHT))?;
+        device.enable(EventCode::EV_REL(EV_REL::REL_X))?;
+        device.enable(EventCode::EV_REL(EV_REL::REL_Y))?;
+        device.enable(EventCode::EV_REL(EV_REL::REL_WHEEL))?;
+
+        UInputDevice::create_from_device(&device)
+    }
+
     ///Return the device node representing this uinput device.
     ///
     /// This relies on `libevdev_uinput_get_syspath()` to provide a valid syspath.
@@ -100,6 +181,202 @@ impl UInputDevice {
             error => Err(io::Error::from_raw_os_error(-error)),
         }
     }
+
+    /// Post a sequence of events through the uinput device.
+    ///
+    /// The events are written in order. If writing an event fails, the
+    /// remaining events are not written and the returned `PartialWrite`
+    /// holds the number of events that were written before the failure,
+    /// so the caller knows in which state the device was left, e.g. to
+    /// release keys that were already pressed. `PartialWrite` converts into
+    /// an `io::Error` of the same kind as the underlying error.
+    ///
+    /// As with `write_event`, the sequence should end with a `SYN_REPORT`.
+    pub fn write_events(&self, events: &[InputEvent]) -> Result<(), PartialWrite> {
+        for (written, event) in events.iter().enumerate() {
+            self.write_event(event)
+                .map_err(|source| PartialWrite { written, source })?;
+        }
+        Ok(())
+    }
+
+    /// Hold down a key for `duration`, emitting repeat events like the
+    /// kernel's autorepeat does.
+    ///
+    /// A press is emitted immediately, followed by a repeat (value 2) event
+    /// after `delay` and then every `period`, and finally a release once
+    /// `duration` has passed. Each event is followed by a `SYN_REPORT`.
+    /// This blocks the calling thread for `duration`.
+    ///
+    /// Returns an error with `InvalidInput` if `period` is zero.
+    pub fn key_hold(
+        &self,
+        key: EV_KEY,
+        duration: Duration,
+        delay: Duration,
+        period: Duration,
+    ) -> io::Result<()> {
+        if period == Duration::from_secs(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the repeat period must not be zero",
+            ));
+        }
+
+        let start = Instant::now();
+
+        self.key(key, KeyState::Pressed)?;
+
+        let mut next_repeat = delay;
+        while next_repeat < duration {
+            sleep_until(start + next_repeat);
+            self.key(key, KeyState::Repeated)?;
+            next_repeat += period;
+        }
+
+        sleep_until(start + duration);
+        self.key(key, KeyState::Released)
+    }
+
+    /// Write an EV_KEY event setting `key` to `state`, followed by a
+    /// `SYN_REPORT`.
+    pub fn key(&self, key: EV_KEY, state: KeyState) -> io::Result<()> {
+        self.write_event(&InputEvent::key(key, state))?;
+        self.write_syn_report()
+    }
+
+    /// Move the pointer by `dx`/`dy`, followed by a `SYN_REPORT`.
+    ///
+    /// Only the axes with a nonzero delta are written, and nothing at all
+    /// is written if both are zero.
+    pub fn move_rel(&self, dx: i32, dy: i32) -> io::Result<()> {
+        if dx == 0 && dy == 0 {
+            return Ok(());
+        }
+
+        if dx != 0 {
+            self.write_event(&InputEvent::rel(EV_REL::REL_X, dx))?;
+        }
+        if dy != 0 {
+            self.write_event(&InputEvent::rel(EV_REL::REL_Y, dy))?;
+        }
+        self.write_syn_report()
+    }
+
+    /// Write an EV_ABS event setting `axis` to `value`, clamped to the range
+    /// `[minimum, maximum]` of `info`, followed by a `SYN_REPORT`.
+    ///
+    /// `info` is usually the abs info the axis was created with. Clamping
+    /// keeps a layer forwarding events from another device from passing on
+    /// values outside of the range the consumers of this device expect.
+    /// Returns an error with `InvalidInput` if `minimum` is greater than
+    /// `maximum`.
+    pub fn write_abs(&self, axis: EV_ABS, value: i32, info: &AbsInfo) -> io::Result<()> {
+        if info.minimum > info.maximum {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the minimum of the axis is greater than its maximum",
+            ));
+        }
+
+        let value = value.clamp(info.minimum, info.maximum);
+        self.write_event(&InputEvent::abs(axis, value))?;
+        self.write_syn_report()
+    }
+
+    /// Scroll the wheel by `clicks` detents, followed by a `SYN_REPORT`.
+    ///
+    /// Positive values scroll up. If the device was created with
+    /// REL_WHEEL_HI_RES, a REL_WHEEL_HI_RES event of 120 units per detent
+    /// is written along with the REL_WHEEL event, as the kernel does for
+    /// high-resolution wheels. Nothing is written if `clicks` is 0.
+    pub fn scroll(&self, clicks: i32) -> io::Result<()> {
+        if clicks == 0 {
+            return Ok(());
+        }
+
+        self.write_event(&InputEvent::rel(EV_REL::REL_WHEEL, clicks))?;
+        if self.hi_res_wheel {
+            self.write_event(&InputEvent::rel(
+                EV_REL::REL_WHEEL_HI_RES,
+                clicks.saturating_mul(120),
+            ))?;
+        }
+        self.write_syn_report()
+    }
+
+    /// Press and release `button`, each followed by a `SYN_REPORT` so the
+    /// press and release are seen in separate frames.
+    pub fn click(&self, button: EV_KEY) -> io::Result<()> {
+        self.key(button, KeyState::Pressed)?;
+        self.key(button, KeyState::Released)
+    }
+
+    /// Bring the device into the given state, e.g. to set up a test
+    /// fixture before the device is read from, followed by a `SYN_REPORT`.
+    ///
+    /// An event is written for every value of `state`, as returned by
+    /// `DeviceState::changes` for an empty state. The kernel discards the
+    /// events for codes the device doesn't support and for values that
+    /// didn't change, so readers only see the differences. Nothing is
+    /// written if `state` is empty.
+    pub fn set_initial_state(&self, state: &DeviceState) -> io::Result<()> {
+        Ok(self.write_events(&DeviceState::new().changes(state))?)
+    }
+
+    fn write_syn_report(&self) -> io::Result<()> {
+        self.write_event(&InputEvent::syn_report())
+    }
+}
+
+fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now {
+        thread::sleep(deadline - now);
+    }
+}
+
+/// The error returned by [UInputDevice::write_events] if not all events
+/// could be written
+#[derive(Debug)]
+pub struct PartialWrite {
+    /// The number of events written before the failure
+    pub written: usize,
+    /// The error writing the next event
+    pub source: io::Error,
+}
+
+impl fmt::Display for PartialWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "failed to write event after {} events: {}",
+            self.written, self.source
+        )
+    }
+}
+
+impl std::error::Error for PartialWrite {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<PartialWrite> for io::Error {
+    fn from(error: PartialWrite) -> io::Error {
+        io::Error::new(error.source.kind(), error)
+    }
+}
+
+fn new_virtual_device(name: &str) -> io::Result<UninitDevice> {
+    let mut device =
+        UninitDevice::new().ok_or_else(|| io::Error::from_raw_os_error(libc::ENOMEM))?;
+
+    device.set_name(name);
+    device.set_bustype(BusType::BUS_VIRTUAL as u16);
+    device.enable(EventCode::EV_SYN(EV_SYN::SYN_REPORT))?;
+
+    Ok(device)
 }
 
 impl Drop for UInputDevice {
@@ -1,12 +1,16 @@
 use crate::enums::*;
 use libc::{c_char, c_uint};
 use log::warn;
+use std::cmp::Ordering;
 use std::ffi::{CStr, CString};
 use std::fmt;
 
 use evdev_sys as raw;
 
-pub(crate) unsafe fn ptr_to_str(ptr: *const c_char) -> Option<&'static str> {
+/// The caller must make sure that the returned `&str` does not outlive the
+/// string pointed to by `ptr`, e.g. by tying its lifetime to the owner of
+/// the string.
+pub(crate) unsafe fn ptr_to_str<'a>(ptr: *const c_char) -> Option<&'a str> {
     let slice = CStr::from_ptr(ptr.as_ref()?);
     let buf = slice.to_bytes();
     std::str::from_utf8(buf).ok()
@@ -90,25 +94,26 @@ pub fn event_code_to_int(event_code: &EventCode) -> (c_uint, c_uint) {
 }
 
 pub fn int_to_event_code(event_type: c_uint, event_code: c_uint) -> EventCode {
-    let ev_type: EventType = int_to_event_type(event_type as u32).unwrap();
     let code = event_code as u32;
 
-    let ev_code = match ev_type {
-        EventType::EV_SYN => int_to_ev_syn(code).map(EventCode::EV_SYN),
-        EventType::EV_KEY => int_to_ev_key(code).map(EventCode::EV_KEY),
-        EventType::EV_ABS => int_to_ev_abs(code).map(EventCode::EV_ABS),
-        EventType::EV_REL => int_to_ev_rel(code).map(EventCode::EV_REL),
-        EventType::EV_MSC => int_to_ev_msc(code).map(EventCode::EV_MSC),
-        EventType::EV_SW => int_to_ev_sw(code).map(EventCode::EV_SW),
-        EventType::EV_LED => int_to_ev_led(code).map(EventCode::EV_LED),
-        EventType::EV_SND => int_to_ev_snd(code).map(EventCode::EV_SND),
-        EventType::EV_REP => int_to_ev_rep(code).map(EventCode::EV_REP),
-        EventType::EV_FF => int_to_ev_ff(code).map(EventCode::EV_FF),
-        EventType::EV_PWR => Some(EventCode::EV_PWR),
-        EventType::EV_FF_STATUS => int_to_ev_ff(code).map(EventCode::EV_FF_STATUS),
-        EventType::EV_UNK => None,
-        EventType::EV_MAX => Some(EventCode::EV_MAX),
-    };
+    // Types beyond EV_MAX are invalid, they are reported as EV_UNK as well
+    let ev_code =
+        int_to_event_type(event_type as u32).and_then(|ev_type| match ev_type {
+            EventType::EV_SYN => int_to_ev_syn(code).map(EventCode::EV_SYN),
+            EventType::EV_KEY => int_to_ev_key(code).map(EventCode::EV_KEY),
+            EventType::EV_ABS => int_to_ev_abs(code).map(EventCode::EV_ABS),
+            EventType::EV_REL => int_to_ev_rel(code).map(EventCode::EV_REL),
+            EventType::EV_MSC => int_to_ev_msc(code).map(EventCode::EV_MSC),
+            EventType::EV_SW => int_to_ev_sw(code).map(EventCode::EV_SW),
+            EventType::EV_LED => int_to_ev_led(code).map(EventCode::EV_LED),
+            EventType::EV_SND => int_to_ev_snd(code).map(EventCode::EV_SND),
+            EventType::EV_REP => int_to_ev_rep(code).map(EventCode::EV_REP),
+            EventType::EV_FF => int_to_ev_ff(code).map(EventCode::EV_FF),
+            EventType::EV_PWR => Some(EventCode::EV_PWR),
+            EventType::EV_FF_STATUS => int_to_ev_ff(code).map(EventCode::EV_FF_STATUS),
+            EventType::EV_UNK => None,
+            EventType::EV_MAX => Some(EventCode::EV_MAX),
+        });
 
     ev_code.unwrap_or(EventCode::EV_UNK {
         event_type,
@@ -116,6 +121,46 @@ pub fn int_to_event_code(event_type: c_uint, event_code: c_uint) -> EventCode {
     })
 }
 
+/// Whether the code is one of the per-slot ABS_MT_* codes, ABS_MT_TOUCH_MAJOR
+/// up to ABS_MT_TOOL_Y
+pub(crate) fn is_mt_code(code: &EventCode) -> bool {
+    match code {
+        EventCode::EV_ABS(abs) => (EV_ABS::ABS_MT_TOUCH_MAJOR as u32
+            ..=EV_ABS::ABS_MT_TOOL_Y as u32)
+            .contains(&(*abs as u32)),
+        _ => false,
+    }
+}
+
+// EventCodes are ordered by their kernel type and code numbers. EV_UNK codes
+// sort by their stored numbers and after a known code with the same numbers.
+fn event_code_sort_key(event_code: &EventCode) -> (c_uint, c_uint, bool) {
+    match *event_code {
+        EventCode::EV_PWR => (EventType::EV_PWR as c_uint, 0, false),
+        EventCode::EV_MAX => (EventType::EV_MAX as c_uint, 0, false),
+        EventCode::EV_UNK {
+            event_type,
+            event_code,
+        } => (event_type as c_uint, event_code as c_uint, true),
+        _ => {
+            let (ev_type, ev_code) = event_code_to_int(event_code);
+            (ev_type, ev_code, false)
+        }
+    }
+}
+
+impl PartialOrd for EventCode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EventCode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        event_code_sort_key(self).cmp(&event_code_sort_key(other))
+    }
+}
+
 impl fmt::Display for EventType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -198,13 +243,88 @@ impl EventCode {
             k => Some(int_to_event_code(*ev_type as u32, k as u32)),
         }
     }
+
+    /// Look up an event code by its name alone (eg., "KEY_A" or "ABS_X"), the
+    /// type is derived from the prefix of the name. Returns `None` if there is
+    /// no event code with this name.
+    pub fn from_name(name: &str) -> Option<EventCode> {
+        let prefix = &name[..name.find('_')?];
+        match prefix {
+            "SYN" => name.parse().ok().map(EventCode::EV_SYN),
+            "KEY" | "BTN" => name.parse().ok().map(EventCode::EV_KEY),
+            "REL" => name.parse().ok().map(EventCode::EV_REL),
+            "ABS" => name.parse().ok().map(EventCode::EV_ABS),
+            "MSC" => name.parse().ok().map(EventCode::EV_MSC),
+            "SW" => name.parse().ok().map(EventCode::EV_SW),
+            "LED" => name.parse().ok().map(EventCode::EV_LED),
+            "SND" => name.parse().ok().map(EventCode::EV_SND),
+            "REP" => name.parse().ok().map(EventCode::EV_REP),
+            "FF" => name.parse().ok().map(EventCode::EV_FF),
+            _ => None,
+        }
+    }
+}
+
+impl EV_KEY {
+    /// Returns `true` if this is a button (`BTN_*`) rather than a keyboard
+    /// key (`KEY_*`).
+    ///
+    /// Buttons are the codes in the `BTN_MISC` to `KEY_OK` range, the
+    /// `BTN_DPAD_*` codes and the `BTN_TRIGGER_HAPPY*` codes.
+    pub fn is_button(&self) -> bool {
+        let code = *self as u32;
+
+        (EV_KEY::BTN_0 as u32..EV_KEY::KEY_OK as u32).contains(&code)
+            || (EV_KEY::BTN_DPAD_UP as u32..=EV_KEY::BTN_DPAD_RIGHT as u32)
+                .contains(&code)
+            || (EV_KEY::BTN_TRIGGER_HAPPY1 as u32..=EV_KEY::BTN_TRIGGER_HAPPY40 as u32)
+                .contains(&code)
+    }
+}
+
+// libevdev's names are static strings, so they live as long as the program
+fn code_name(code: &EventCode) -> Option<&'static str> {
+    let (ev_type, ev_code) = event_code_to_int(code);
+    unsafe { ptr_to_str(raw::libevdev_event_code_get_name(ev_type, ev_code)) }
+}
+
+macro_rules! all_names {
+    ($($ev_type:ident),*) => {
+        $(
+            impl $ev_type {
+                /// Iterate over every code of this type along with its name, in
+                /// the order of the codes, e.g. to offer completions for a
+                /// config file.
+                pub fn all_names() -> impl Iterator<Item = ($ev_type, &'static str)> {
+                    EventCodeIterator::new(&EventType::$ev_type).filter_map(|code| {
+                        match code {
+                            EventCode::$ev_type(c) => Some((c, code_name(&code)?)),
+                            _ => None,
+                        }
+                    })
+                }
+            }
+        )*
+    };
 }
 
+all_names!(EV_SYN, EV_KEY, EV_REL, EV_ABS, EV_MSC, EV_SW, EV_LED, EV_SND, EV_REP, EV_FF);
+
 impl InputProp {
     pub fn iter(&self) -> InputPropIterator {
         InputPropIterator { current: *self }
     }
 
+    /// Iterate over every property along with its name, in the order of
+    /// the properties.
+    pub fn all_names() -> impl Iterator<Item = (InputProp, &'static str)> {
+        InputPropIterator::new().filter_map(|prop| {
+            let name =
+                unsafe { ptr_to_str(raw::libevdev_property_get_name(prop as c_uint)) }?;
+            Some((prop, name))
+        })
+    }
+
     /// Look up an input property by its name. Properties start with the fixed
     /// prefix "INPUT_PROP_" followed by their name (eg., "INPUT_PROP_POINTER").
     /// The prefix must be included in the name. It returns the constant assigned
@@ -244,6 +364,11 @@ impl Iterator for EventTypeIterator {
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = EventType::EV_MAX as usize - self.current as usize;
+        (remaining.min(1), Some(remaining))
+    }
 }
 
 impl Iterator for EventCodeIterator {
@@ -414,6 +539,23 @@ impl Iterator for EventCodeIterator {
             _ => None,
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match self.current {
+            EventCode::EV_SYN(code) => EV_SYN::SYN_MAX as usize - code as usize,
+            EventCode::EV_KEY(code) => EV_KEY::KEY_MAX as usize - code as usize,
+            EventCode::EV_REL(code) => EV_REL::REL_MAX as usize - code as usize,
+            EventCode::EV_ABS(code) => EV_ABS::ABS_MAX as usize - code as usize,
+            EventCode::EV_MSC(code) => EV_MSC::MSC_MAX as usize - code as usize,
+            EventCode::EV_SW(code) => EV_SW::SW_MAX as usize - code as usize,
+            EventCode::EV_LED(code) => EV_LED::LED_MAX as usize - code as usize,
+            EventCode::EV_SND(code) => EV_SND::SND_MAX as usize - code as usize,
+            EventCode::EV_REP(code) => EV_REP::REP_MAX as usize - code as usize,
+            EventCode::EV_FF(code) => EV_FF::FF_MAX as usize - code as usize,
+            _ => 0,
+        };
+        (remaining.min(1), Some(remaining))
+    }
 }
 
 impl Iterator for InputPropIterator {
@@ -437,4 +579,9 @@ impl Iterator for InputPropIterator {
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = InputProp::INPUT_PROP_MAX as usize - self.current as usize;
+        (remaining.min(1), Some(remaining))
+    }
 }
@@ -46,9 +46,24 @@ fn context_grab() {
     d.grab(GrabMode::Ungrab).unwrap();
 }
 
+#[test]
+fn context_revoke() {
+    let mut d = Device::new_from_path("/dev/input/event0").unwrap();
+    d.revoke().unwrap();
+    let err = d.next_event(ReadFlag::NORMAL).err().unwrap();
+    assert_eq!(err.raw_os_error(), Some(libc::ENODEV));
+}
+
+#[test]
+fn context_close() {
+    let mut d = Device::new_from_path("/dev/input/event0").unwrap();
+    d.grab(GrabMode::Grab).unwrap();
+    d.close().unwrap();
+}
+
 #[test]
 fn device_get_name() {
-    let d = UninitDevice::new().unwrap();
+    let mut d = UninitDevice::new().unwrap();
 
     d.set_name("hello");
     assert_eq!(d.name().unwrap(), "hello");
@@ -56,7 +71,7 @@ fn device_get_name() {
 
 #[test]
 fn device_get_uniq() {
-    let d = UninitDevice::new().unwrap();
+    let mut d = UninitDevice::new().unwrap();
 
     d.set_uniq("test");
     assert_eq!(d.uniq().unwrap(), "test");
@@ -64,7 +79,7 @@ fn device_get_uniq() {
 
 #[test]
 fn device_get_phys() {
-    let d = UninitDevice::new().unwrap();
+    let mut d = UninitDevice::new().unwrap();
 
     d.set_phys("test");
     assert_eq!(d.phys().unwrap(), "test");
@@ -188,3 +203,2646 @@ fn test_timeval() {
     assert_eq!(TimeVal::new(1, -1_000_000), TimeVal::new(0, 0));
     assert_eq!(TimeVal::new(-100, 1_000_000 * 100), TimeVal::new(0, 0));
 }
+
+#[test]
+fn device_clone_from_filtered() {
+    let d = UninitDevice::new().unwrap();
+    let absinfo = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 0,
+        flat: 0,
+        resolution: 10,
+    };
+
+    d.enable(EventCode::EV_SYN(EV_SYN::SYN_REPORT)).unwrap();
+    d.enable(EventCode::EV_REL(EV_REL::REL_X)).unwrap();
+    d.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT)).unwrap();
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_X),
+        Some(EnableCodeData::AbsInfo(absinfo)),
+    )
+    .unwrap();
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_Y),
+        Some(EnableCodeData::AbsInfo(absinfo)),
+    )
+    .unwrap();
+
+    let clone = UninitDevice::clone_from_filtered(&d, |code| {
+        matches!(
+            code,
+            EventCode::EV_REL(_) | EventCode::EV_ABS(EV_ABS::ABS_X)
+        )
+    })
+    .unwrap();
+
+    assert!(clone.has(EventCode::EV_SYN(EV_SYN::SYN_REPORT)));
+    assert!(clone.has(EventCode::EV_REL(EV_REL::REL_X)));
+    assert!(!clone.has(EventCode::EV_KEY(EV_KEY::BTN_LEFT)));
+    assert!(!clone.has(EventType::EV_KEY));
+    assert!(!clone.has(EventCode::EV_ABS(EV_ABS::ABS_Y)));
+
+    let clone_absinfo = clone.abs_info(&EventCode::EV_ABS(EV_ABS::ABS_X)).unwrap();
+    assert_eq!(clone_absinfo.maximum, 1000);
+    assert_eq!(clone_absinfo.resolution, 10);
+}
+
+#[test]
+fn device_is_direct_is_pointer() {
+    let d = UninitDevice::new().unwrap();
+    assert!(!d.is_direct());
+    assert!(!d.is_pointer());
+
+    d.enable_property(&InputProp::INPUT_PROP_DIRECT).unwrap();
+    assert!(d.is_direct());
+    assert!(!d.is_pointer());
+}
+
+#[test]
+fn iterator_size_hint() {
+    let types = EventTypeIterator::new();
+    let (lower, upper) = types.size_hint();
+    let count = types.count();
+    assert!(lower <= count && count <= upper.unwrap());
+
+    let codes = EventCodeIterator::new(&EventType::EV_KEY);
+    let (lower, upper) = codes.size_hint();
+    let count = codes.count();
+    assert!(lower <= count && count <= upper.unwrap());
+
+    let props = InputPropIterator::new();
+    let (lower, upper) = props.size_hint();
+    let count = props.count();
+    assert!(lower <= count && count <= upper.unwrap());
+
+    let mut exhausted = EventTypeIterator::new();
+    exhausted.by_ref().for_each(drop);
+    assert_eq!(exhausted.size_hint(), (0, Some(0)));
+}
+
+#[test]
+fn value_history() {
+    let time = TimeVal::new(0, 0);
+    let rel_x = EventCode::EV_REL(EV_REL::REL_X);
+    let rel_y = EventCode::EV_REL(EV_REL::REL_Y);
+    let mut history = ValueHistory::new(3);
+
+    assert!(history.recent(&rel_x).is_empty());
+
+    for value in 1..=5 {
+        history.record(&InputEvent::new(&time, &rel_x, value));
+    }
+    history.record(&InputEvent::new(&time, &rel_y, -1));
+
+    assert_eq!(history.recent(&rel_x), &[3, 4, 5]);
+    assert_eq!(history.recent(&rel_y), &[-1]);
+
+    history.clear();
+    assert!(history.recent(&rel_x).is_empty());
+
+    let mut disabled = ValueHistory::new(0);
+    disabled.record(&InputEvent::new(&time, &rel_x, 1));
+    assert!(disabled.recent(&rel_x).is_empty());
+}
+
+#[test]
+fn device_modalias() {
+    let d = UninitDevice::new().unwrap();
+    d.set_bustype(BusType::BUS_USB as u16);
+    d.set_vendor_id(0x046d);
+    d.set_product_id(0xc52b);
+    d.set_version(0x0111);
+    d.enable(EventCode::EV_SYN(EV_SYN::SYN_REPORT)).unwrap();
+    d.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT)).unwrap();
+    d.enable(EventCode::EV_REL(EV_REL::REL_X)).unwrap();
+    d.enable(EventCode::EV_REL(EV_REL::REL_Y)).unwrap();
+
+    assert_eq!(
+        d.modalias(),
+        "input:b0003v046DpC52Be0111-e0,1,2,k110,r0,1,amlsfw"
+    );
+}
+
+#[test]
+fn uinput_virtual_keyboard() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::KEY_A)));
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::KEY_MICMUTE)));
+    assert!(!d.has(EventCode::EV_KEY(EV_KEY::BTN_LEFT)));
+}
+
+#[test]
+fn uinput_virtual_mouse() {
+    let mouse = UInputDevice::virtual_mouse().unwrap();
+    let d = Device::new_from_path(mouse.devnode().unwrap()).unwrap();
+
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::BTN_LEFT)));
+    assert!(d.has(EventCode::EV_REL(EV_REL::REL_X)));
+    assert!(d.has(EventCode::EV_REL(EV_REL::REL_WHEEL)));
+}
+
+#[test]
+fn device_event_mask() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    let codes = [EventCode::EV_KEY(EV_KEY::KEY_A)];
+
+    d.set_event_mask(&EventType::EV_KEY, &codes).unwrap();
+    assert_eq!(d.event_mask(&EventType::EV_KEY).unwrap(), codes);
+
+    d.set_event_mask(&EventType::EV_MSC, &[]).unwrap();
+    assert!(d.event_mask(&EventType::EV_MSC).unwrap().is_empty());
+
+    assert!(d
+        .set_event_mask(&EventType::EV_KEY, &[EventCode::EV_REL(EV_REL::REL_X)])
+        .is_err());
+
+    d.set_event_type_mask(&[EventType::EV_KEY, EventType::EV_REL])
+        .unwrap();
+    assert_eq!(
+        d.event_type_mask().unwrap(),
+        vec![EventType::EV_KEY, EventType::EV_REL]
+    );
+    d.set_event_mask(&EventType::EV_SYN, &[EventCode::EV_KEY(EV_KEY::KEY_A)])
+        .unwrap();
+    assert_eq!(
+        d.event_mask(&EventType::EV_SYN).unwrap(),
+        vec![EventCode::EV_KEY(EV_KEY::KEY_RESERVED)]
+    );
+}
+
+#[test]
+fn device_flush() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    d.flush().unwrap();
+    assert!(!d.has_event_pending());
+}
+
+#[test]
+fn event_code_ordering() {
+    let mut expected = Vec::new();
+    for ev_type in EventTypeIterator::new() {
+        expected.extend(EventCodeIterator::new(&ev_type));
+    }
+
+    let mut codes = expected.clone();
+    codes.reverse();
+    codes.sort();
+    assert_eq!(codes, expected);
+
+    let unknown = EventCode::EV_UNK {
+        event_type: EventType::EV_KEY as u32,
+        event_code: EV_KEY::KEY_A as u32,
+    };
+    assert!(EventCode::EV_KEY(EV_KEY::KEY_A) < unknown);
+    assert!(unknown < EventCode::EV_KEY(EV_KEY::KEY_S));
+    assert!(EventCode::EV_KEY(EV_KEY::KEY_MAX) < EventCode::EV_REL(EV_REL::REL_X));
+}
+
+#[test]
+fn device_sync_pending() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    assert!(!d.sync_pending());
+
+    let (status, _) = d.next_event(ReadFlag::FORCE_SYNC).unwrap();
+    assert!(status == ReadStatus::Sync);
+    assert!(d.sync_pending());
+
+    while let Ok((ReadStatus::Sync, _)) = d.next_event(ReadFlag::SYNC) {}
+    assert!(!d.sync_pending());
+}
+
+#[test]
+fn uinput_key_hold() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let ms = std::time::Duration::from_millis;
+
+    keyboard
+        .key_hold(EV_KEY::KEY_A, ms(30), ms(10), ms(5))
+        .unwrap();
+    assert!(keyboard
+        .key_hold(EV_KEY::KEY_A, ms(30), ms(10), ms(0))
+        .is_err());
+}
+
+#[test]
+fn ev_key_is_button() {
+    assert!(EV_KEY::BTN_LEFT.is_button());
+    assert!(EV_KEY::BTN_0.is_button());
+    assert!(EV_KEY::BTN_GEAR_UP.is_button());
+    assert!(EV_KEY::BTN_DPAD_UP.is_button());
+    assert!(EV_KEY::BTN_TRIGGER_HAPPY40.is_button());
+
+    assert!(!EV_KEY::KEY_A.is_button());
+    assert!(!EV_KEY::KEY_MICMUTE.is_button());
+    assert!(!EV_KEY::KEY_OK.is_button());
+    assert!(!EV_KEY::KEY_MAX.is_button());
+}
+
+#[test]
+fn device_sysfs_name() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    assert_eq!(d.sysfs_name().unwrap(), d.name().unwrap());
+}
+
+#[test]
+fn input_event_unknown_code() {
+    let time = TimeVal::new(1, 0);
+    for &(event_type, event_code) in &[(0x1c, 5), (0x40, 0x200)] {
+        let code = EventCode::EV_UNK {
+            event_type,
+            event_code,
+        };
+        let ev = InputEvent::new(&time, &code, 42);
+
+        let raw = ev.as_raw();
+        assert_eq!(raw.type_ as u32, event_type);
+        assert_eq!(raw.code as u32, event_code);
+        assert_eq!(InputEvent::from_raw(&raw), ev);
+    }
+}
+
+#[test]
+fn device_display_name() {
+    let mut d = UninitDevice::new().unwrap();
+    assert_eq!(d.display_name(), "Unknown Device");
+
+    d.set_name("hello");
+    assert_eq!(d.display_name(), "hello");
+}
+
+#[test]
+fn device_state_snapshot() {
+    let d = UninitDevice::new().unwrap();
+    let key = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let rel = EventCode::EV_REL(EV_REL::REL_X);
+    d.enable(key).unwrap();
+    d.enable(rel).unwrap();
+    d.set_event_value(&key, 1).unwrap();
+
+    let state = DeviceState::from_device(&d);
+    assert_eq!(state.value(&key), Some(1));
+    assert_eq!(state.value(&rel), None);
+    assert_eq!(state.num_slots(), 0);
+
+    d.set_event_value(&key, 0).unwrap();
+    assert_eq!(state.value(&key), Some(1));
+}
+
+#[test]
+fn context_set_file_with_snapshot() {
+    let d = UninitDevice::new().unwrap();
+    let f = File::open("/dev/input/event0").unwrap();
+    let (d, state) = d.set_file_with_snapshot(f).unwrap();
+    assert_eq!(state, DeviceState::from_device(&d));
+}
+
+#[test]
+fn device_event_types() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    let types: Vec<_> = d.event_types().collect();
+    assert!(types.contains(&EventType::EV_SYN));
+    for ev_type in EventTypeIterator::new() {
+        assert_eq!(types.contains(&ev_type), d.has(ev_type));
+    }
+}
+
+#[test]
+fn absinfo_wrapping_add() {
+    let info = AbsInfo {
+        value: 0,
+        minimum: -5,
+        maximum: 5,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+
+    assert_eq!(info.wrapping_add(0, 3), 3);
+    assert_eq!(info.wrapping_add(5, 0), 5);
+    assert_eq!(info.wrapping_add(5, 1), -5);
+    assert_eq!(info.wrapping_add(-5, -1), 5);
+    assert_eq!(info.wrapping_add(4, 13), -5);
+    assert_eq!(info.wrapping_add(-5, -22), -5);
+
+    let full = AbsInfo {
+        minimum: i32::MIN,
+        maximum: i32::MAX,
+        ..info
+    };
+    assert_eq!(full.wrapping_add(i32::MAX, 1), i32::MIN);
+    assert_eq!(full.wrapping_add(i32::MIN, -1), i32::MAX);
+
+    let single = AbsInfo {
+        minimum: 7,
+        maximum: 7,
+        ..info
+    };
+    assert_eq!(single.wrapping_add(7, 100), 7);
+}
+
+#[test]
+fn device_touch_frame() {
+    let d = UninitDevice::new().unwrap();
+    assert!(d.touch_frame().touches.is_empty());
+
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let slots = AbsInfo { maximum: 2, ..info };
+    let ids = AbsInfo {
+        minimum: -1,
+        maximum: 0xffff,
+        ..info
+    };
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT),
+        Some(EnableCodeData::AbsInfo(slots)),
+    )
+    .unwrap();
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_MT_TRACKING_ID),
+        Some(EnableCodeData::AbsInfo(ids)),
+    )
+    .unwrap();
+    for abs in &[EV_ABS::ABS_MT_POSITION_X, EV_ABS::ABS_MT_POSITION_Y] {
+        d.enable_event_code(
+            &EventCode::EV_ABS(*abs),
+            Some(EnableCodeData::AbsInfo(info)),
+        )
+        .unwrap();
+    }
+
+    let id = EventCode::EV_ABS(EV_ABS::ABS_MT_TRACKING_ID);
+    for slot in 0..3 {
+        d.set_slot_value(slot, &id, -1).unwrap();
+    }
+    d.set_slot_value(1, &id, 42).unwrap();
+    d.set_slot_value(1, &EventCode::EV_ABS(EV_ABS::ABS_MT_POSITION_X), 10)
+        .unwrap();
+    d.set_slot_value(1, &EventCode::EV_ABS(EV_ABS::ABS_MT_POSITION_Y), 20)
+        .unwrap();
+
+    let frame = d.touch_frame();
+    assert_eq!(
+        frame.touches,
+        vec![Touch {
+            slot: 1,
+            id: 42,
+            x: 10,
+            y: 20,
+            pressure: None,
+            major: None,
+            minor: None,
+        }]
+    );
+}
+
+#[test]
+fn device_kernel_device_id() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    let id = d.kernel_device_id().unwrap();
+    assert_eq!(id.bustype as u16, d.bustype());
+    assert_eq!(id.vendor, d.vendor_id());
+    assert_eq!(id.product, d.product_id());
+    assert_eq!(id.version, d.version());
+}
+
+#[test]
+fn device_keycode() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    if !d.has(EventType::EV_KEY) {
+        return;
+    }
+
+    // The scancode of KEY_A on AT keyboards
+    let scancode = 0x1e;
+    if let Ok(keycode) = d.keycode(scancode) {
+        if let Some(key) = int_to_ev_key(keycode) {
+            d.set_keycode(scancode, key).unwrap();
+            assert_eq!(d.keycode(scancode).unwrap(), keycode);
+        }
+    }
+}
+
+#[test]
+fn absinfo_in_deadzone() {
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 255,
+        fuzz: 0,
+        flat: 15,
+        resolution: 0,
+    };
+    assert!(info.in_deadzone(127));
+    assert!(info.in_deadzone(112));
+    assert!(info.in_deadzone(142));
+    assert!(!info.in_deadzone(111));
+    assert!(!info.in_deadzone(143));
+    assert!(!info.in_deadzone(15));
+
+    let asymmetric = AbsInfo {
+        minimum: -100,
+        maximum: 50,
+        flat: 10,
+        ..info
+    };
+    assert!(asymmetric.in_deadzone(-25));
+    assert!(asymmetric.in_deadzone(-35));
+    assert!(asymmetric.in_deadzone(-15));
+    assert!(!asymmetric.in_deadzone(-36));
+    assert!(!asymmetric.in_deadzone(0));
+
+    let no_flat = AbsInfo { flat: 0, ..info };
+    assert!(no_flat.in_deadzone(127));
+    assert!(!no_flat.in_deadzone(128));
+}
+
+#[test]
+fn device_from_spec() {
+    let stick = AbsInfo {
+        value: 0,
+        minimum: -32768,
+        maximum: 32767,
+        fuzz: 16,
+        flat: 128,
+        resolution: 0,
+    };
+    let trigger = AbsInfo {
+        minimum: 0,
+        maximum: 255,
+        fuzz: 0,
+        flat: 0,
+        ..stick
+    };
+    let spec = DeviceSpec {
+        name: "test gamepad".to_owned(),
+        id: DeviceId {
+            bustype: BusType::BUS_USB,
+            vendor: 0x045e,
+            product: 0x028e,
+            version: 0x0110,
+        },
+        codes: vec![
+            (EventCode::EV_KEY(EV_KEY::BTN_SOUTH), None),
+            (
+                EventCode::EV_ABS(EV_ABS::ABS_X),
+                Some(EnableCodeData::AbsInfo(stick)),
+            ),
+            (
+                EventCode::EV_ABS(EV_ABS::ABS_Z),
+                Some(EnableCodeData::AbsInfo(trigger)),
+            ),
+            (
+                EventCode::EV_REP(EV_REP::REP_DELAY),
+                Some(EnableCodeData::RepInfo(250)),
+            ),
+        ],
+    };
+
+    let d = UninitDevice::from_spec(&spec).unwrap();
+    assert_eq!(d.name(), Some("test gamepad"));
+    assert_eq!(d.bustype(), BusType::BUS_USB as u16);
+    assert_eq!(d.vendor_id(), 0x045e);
+    assert_eq!(d.product_id(), 0x028e);
+    assert_eq!(d.version(), 0x0110);
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::BTN_SOUTH)));
+
+    let x = d.abs_info(&EventCode::EV_ABS(EV_ABS::ABS_X)).unwrap();
+    assert_eq!(
+        (x.minimum, x.maximum, x.fuzz, x.flat),
+        (-32768, 32767, 16, 128)
+    );
+    let z = d.abs_info(&EventCode::EV_ABS(EV_ABS::ABS_Z)).unwrap();
+    assert_eq!((z.minimum, z.maximum), (0, 255));
+    assert_eq!(
+        d.event_value(&EventCode::EV_REP(EV_REP::REP_DELAY)),
+        Some(250)
+    );
+
+    let missing_absinfo = DeviceSpec {
+        codes: vec![(EventCode::EV_ABS(EV_ABS::ABS_Y), None)],
+        ..spec
+    };
+    assert!(UninitDevice::from_spec(&missing_absinfo).is_err());
+}
+
+#[test]
+fn device_slot_range() {
+    let d = UninitDevice::new().unwrap();
+    assert_eq!(d.slot_range(), None);
+
+    let slots = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 4,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT),
+        Some(EnableCodeData::AbsInfo(slots)),
+    )
+    .unwrap();
+    assert_eq!(d.slot_range(), Some(0..5));
+}
+
+#[test]
+fn device_set_abs_info() {
+    let d = UninitDevice::new().unwrap();
+    let code = EventCode::EV_ABS(EV_ABS::ABS_X);
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 100,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    d.enable_event_code(&code, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+
+    d.set_abs_info(
+        &code,
+        &AbsInfo {
+            value: 50,
+            minimum: -10,
+            maximum: 1000,
+            resolution: 12,
+            ..info
+        },
+    );
+    let new_info = d.abs_info(&code).unwrap();
+    assert_eq!(new_info.value, 50);
+    assert_eq!(new_info.minimum, -10);
+    assert_eq!(new_info.maximum, 1000);
+    assert_eq!(new_info.resolution, 12);
+    assert_eq!(d.abs_minimum(EV_ABS::ABS_X as u32).unwrap(), -10);
+    assert_eq!(d.abs_maximum(EV_ABS::ABS_X as u32).unwrap(), 1000);
+    assert_eq!(d.event_value(&code), Some(50));
+
+    // Codes the device doesn't have are left alone
+    let y = EventCode::EV_ABS(EV_ABS::ABS_Y);
+    d.set_abs_info(&y, &info);
+    assert!(d.abs_info(&y).is_none());
+}
+
+#[test]
+fn input_event_same_event() {
+    let code = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let a = InputEvent::new(&TimeVal::new(1, 0), &code, 1);
+    let b = InputEvent::new(&TimeVal::new(2, 500), &code, 1);
+    assert!(a.same_event(&b));
+    assert_ne!(a, b);
+
+    let c = InputEvent::new(&TimeVal::new(1, 0), &code, 0);
+    assert!(!a.same_event(&c));
+    let d = InputEvent::new(&TimeVal::new(1, 0), &EventCode::EV_KEY(EV_KEY::KEY_B), 1);
+    assert!(!a.same_event(&d));
+}
+
+#[test]
+fn device_led_states() {
+    let d = UninitDevice::new().unwrap();
+    assert!(d.led_states().is_empty());
+
+    let caps = EventCode::EV_LED(EV_LED::LED_CAPSL);
+    d.enable(EventCode::EV_LED(EV_LED::LED_NUML)).unwrap();
+    d.enable(caps).unwrap();
+    d.set_event_value(&caps, 1).unwrap();
+
+    assert_eq!(
+        d.led_states(),
+        vec![(EV_LED::LED_NUML, false), (EV_LED::LED_CAPSL, true)]
+    );
+}
+
+#[test]
+fn input_event_bytes() {
+    let ev = InputEvent::new(
+        &TimeVal::new(1_600_000_000, 123_456),
+        &EventCode::EV_ABS(EV_ABS::ABS_Y),
+        -42,
+    );
+    let bytes = ev.to_bytes();
+    assert_eq!(bytes.len(), std::mem::size_of::<libc::input_event>());
+    assert_eq!(InputEvent::from_bytes(&bytes), Some(ev.clone()));
+
+    // Unaligned input
+    let mut buffer = vec![0u8; bytes.len() + 1];
+    buffer[1..].copy_from_slice(&bytes);
+    assert_eq!(InputEvent::from_bytes(&buffer[1..]), Some(ev));
+
+    assert_eq!(InputEvent::from_bytes(&bytes[1..]), None);
+    assert_eq!(InputEvent::from_bytes(&buffer), None);
+}
+
+#[test]
+fn uinput_move_rel_click() {
+    let mouse = UInputDevice::virtual_mouse().unwrap();
+    let d = Device::new_from_path(mouse.devnode().unwrap()).unwrap();
+
+    mouse.move_rel(0, 0).unwrap();
+    mouse.move_rel(0, -5).unwrap();
+    mouse.click(EV_KEY::BTN_LEFT).unwrap();
+
+    let mut events = Vec::new();
+    while d.has_event_pending() {
+        let (_, ev) = d.next_event(ReadFlag::NORMAL).unwrap();
+        events.push((ev.event_code, ev.value));
+    }
+
+    let syn = EventCode::EV_SYN(EV_SYN::SYN_REPORT);
+    let left = EventCode::EV_KEY(EV_KEY::BTN_LEFT);
+    assert_eq!(
+        events,
+        vec![
+            (EventCode::EV_REL(EV_REL::REL_Y), -5),
+            (syn, 0),
+            (left, 1),
+            (syn, 0),
+            (left, 0),
+            (syn, 0),
+        ]
+    );
+}
+
+#[test]
+fn device_meets() {
+    let d = UninitDevice::new().unwrap();
+    assert!(!d.meets(&DeviceClass::Pointer));
+
+    for code in DeviceClass::Pointer.required_codes() {
+        d.enable(code).unwrap();
+    }
+    assert!(d.meets(&DeviceClass::Pointer));
+    assert!(!d.meets(&DeviceClass::Keyboard));
+
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    for code in DeviceClass::Touchscreen.required_codes() {
+        let data = match code {
+            EventCode::EV_ABS(_) => Some(EnableCodeData::AbsInfo(info)),
+            _ => None,
+        };
+        d.enable_event_code(&code, data).unwrap();
+    }
+    assert!(!d.meets(&DeviceClass::Touchscreen));
+    d.enable_property(&InputProp::INPUT_PROP_DIRECT).unwrap();
+    assert!(d.meets(&DeviceClass::Touchscreen));
+
+    let keyboard = DeviceClass::Keyboard.required_codes();
+    assert_eq!(keyboard.len(), 31);
+    assert!(keyboard.contains(&EventCode::EV_KEY(EV_KEY::KEY_ESC)));
+    assert!(keyboard.contains(&EventCode::EV_KEY(EV_KEY::KEY_S)));
+}
+
+#[test]
+fn uinput_phys() {
+    let mut u = UninitDevice::new().unwrap();
+    u.set_name("evdev-rs phys test");
+    u.set_phys("evdev-rs/test0");
+    u.enable(EventCode::EV_KEY(EV_KEY::KEY_A)).unwrap();
+
+    let uinput = UInputDevice::create_from_device(&u).unwrap();
+    let d = Device::new_from_path(uinput.devnode().unwrap()).unwrap();
+    assert_eq!(d.phys(), Some("evdev-rs/test0"));
+    assert_eq!(d.name(), Some("evdev-rs phys test"));
+}
+
+#[test]
+fn device_next_event_timeout() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    let timeout = std::time::Duration::from_millis(20);
+
+    assert!(d
+        .next_event_timeout(ReadFlag::NORMAL, timeout)
+        .unwrap()
+        .is_none());
+
+    keyboard
+        .write_event(&InputEvent::new(
+            &TimeVal::new(0, 0),
+            &EventCode::EV_KEY(EV_KEY::KEY_A),
+            1,
+        ))
+        .unwrap();
+    keyboard
+        .write_event(&InputEvent::new(
+            &TimeVal::new(0, 0),
+            &EventCode::EV_SYN(EV_SYN::SYN_REPORT),
+            0,
+        ))
+        .unwrap();
+
+    let (status, ev) = d
+        .next_event_timeout(ReadFlag::NORMAL, timeout)
+        .unwrap()
+        .unwrap();
+    assert!(status == ReadStatus::Success);
+    assert_eq!(ev.event_code, EventCode::EV_KEY(EV_KEY::KEY_A));
+}
+
+#[test]
+fn device_capabilities_match() {
+    let a = UninitDevice::new().unwrap();
+    let mut b = UninitDevice::new().unwrap();
+    assert!(a.capabilities_match(&b));
+
+    for d in &[&a, &b] {
+        d.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT)).unwrap();
+        d.enable(EventCode::EV_REL(EV_REL::REL_X)).unwrap();
+    }
+    b.set_name("other name");
+    b.set_event_value(&EventCode::EV_KEY(EV_KEY::BTN_LEFT), 1)
+        .unwrap();
+    assert!(a.capabilities_match(&b));
+
+    b.enable(EventCode::EV_REL(EV_REL::REL_Y)).unwrap();
+    assert!(!a.capabilities_match(&b));
+    assert!(!b.capabilities_match(&a));
+
+    b.disable(EventCode::EV_REL(EV_REL::REL_Y)).unwrap();
+    assert!(a.capabilities_match(&b));
+
+    b.enable_property(&InputProp::INPUT_PROP_POINTER).unwrap();
+    assert!(!a.capabilities_match(&b));
+}
+
+#[test]
+fn uinput_write_events() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let time = TimeVal::new(0, 0);
+    let syn = InputEvent::new(&time, &EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0);
+    let press = InputEvent::new(&time, &EventCode::EV_KEY(EV_KEY::KEY_A), 1);
+    let release = InputEvent::new(&time, &EventCode::EV_KEY(EV_KEY::KEY_A), 0);
+
+    keyboard
+        .write_events(&[press.clone(), syn.clone(), release, syn])
+        .unwrap();
+
+    // libevdev rejects event types beyond EV_MAX
+    let invalid = EventCode::EV_UNK {
+        event_type: 0x40,
+        event_code: 0,
+    };
+    let invalid = InputEvent::new(&time, &invalid, 1);
+    let error = keyboard.write_events(&[press, invalid]).unwrap_err();
+    assert_eq!(error.written, 1);
+
+    let error: std::io::Error = error.into();
+    assert!(error.get_ref().unwrap().is::<PartialWrite>());
+}
+
+#[test]
+fn device_new_from_borrowed_fd() {
+    use std::os::unix::io::AsFd;
+
+    let f = File::open("/dev/input/event0").unwrap();
+    {
+        let d = Device::new_from_borrowed_fd(f.as_fd()).unwrap();
+        assert_eq!(d.as_raw_fd(), f.as_raw_fd());
+        assert!(d.has(EventType::EV_SYN));
+    }
+
+    // The file is still open after dropping the device
+    let d = Device::new_from_file(f).unwrap();
+    assert!(d.has(EventType::EV_SYN));
+}
+
+#[test]
+fn device_codes_by_type() {
+    let d = UninitDevice::new().unwrap();
+    d.enable(EventCode::EV_KEY(EV_KEY::BTN_RIGHT)).unwrap();
+    d.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT)).unwrap();
+    d.enable(EventCode::EV_REL(EV_REL::REL_WHEEL)).unwrap();
+    d.enable(EventType::EV_MSC).unwrap();
+
+    let codes = d.codes_by_type();
+    assert_eq!(
+        codes.get(&EventType::EV_KEY),
+        Some(&vec![
+            EventCode::EV_KEY(EV_KEY::BTN_LEFT),
+            EventCode::EV_KEY(EV_KEY::BTN_RIGHT)
+        ])
+    );
+    assert_eq!(
+        codes.get(&EventType::EV_REL),
+        Some(&vec![EventCode::EV_REL(EV_REL::REL_WHEEL)])
+    );
+    assert_eq!(codes.get(&EventType::EV_MSC), Some(&vec![]));
+    assert_eq!(codes.get(&EventType::EV_ABS), None);
+}
+
+/// Collects the messages libevdev logs through `log` for a device
+struct CapturingLogger(std::sync::Mutex<Vec<(log::Level, String)>>);
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let message = record.args().to_string();
+        self.0.lock().unwrap().push((record.level(), message));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger(std::sync::Mutex::new(Vec::new()));
+
+#[test]
+fn device_set_log_priority() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let quiet = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    let verbose = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    verbose.set_log_priority(logging::LogPriority::Debug);
+    verbose.set_log_priority(logging::LogPriority::Error);
+
+    // Reading without a read mode is a bug libevdev logs as an error
+    let invalid_flags = |d: &Device| d.next_event(ReadFlag::BLOCKING).is_err();
+    let captured = || {
+        let messages = LOGGER.0.lock().unwrap();
+        messages
+            .iter()
+            .filter(|(_, message)| message.contains("invalid flags"))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    // Without a device handler the message goes to libevdev's global handler
+    assert!(invalid_flags(&quiet));
+    assert!(captured().is_empty());
+
+    assert!(invalid_flags(&verbose));
+    let messages = captured();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].0, log::Level::Error);
+}
+
+#[test]
+fn device_active_tool() {
+    let d = UninitDevice::new().unwrap();
+    let pen = EventCode::EV_KEY(EV_KEY::BTN_TOOL_PEN);
+    let rubber = EventCode::EV_KEY(EV_KEY::BTN_TOOL_RUBBER);
+    d.enable(pen).unwrap();
+    d.enable(rubber).unwrap();
+    d.enable(EventCode::EV_KEY(EV_KEY::BTN_TOUCH)).unwrap();
+    assert_eq!(d.active_tool(), None);
+
+    d.set_event_value(&EventCode::EV_KEY(EV_KEY::BTN_TOUCH), 1)
+        .unwrap();
+    assert_eq!(d.active_tool(), None);
+
+    d.set_event_value(&rubber, 1).unwrap();
+    assert_eq!(d.active_tool(), Some(EV_KEY::BTN_TOOL_RUBBER));
+
+    d.set_event_value(&pen, 1).unwrap();
+    assert_eq!(d.active_tool(), Some(EV_KEY::BTN_TOOL_PEN));
+}
+
+#[test]
+fn device_calibration() {
+    let d = UninitDevice::new().unwrap();
+    let info = AbsInfo {
+        value: 5,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 4,
+        flat: 0,
+        resolution: 10,
+    };
+    for abs in &[EV_ABS::ABS_X, EV_ABS::ABS_MT_SLOT] {
+        d.enable_event_code(
+            &EventCode::EV_ABS(*abs),
+            Some(EnableCodeData::AbsInfo(info)),
+        )
+        .unwrap();
+    }
+
+    let calibration = Calibration::from_device(&d);
+    assert_eq!(calibration.axes.len(), 1);
+    assert_eq!(calibration.axes.get(&EV_ABS::ABS_X), Some(&info));
+}
+
+#[test]
+fn device_apply_calibration() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    let calibration = Calibration::from_device(&d);
+    d.apply_calibration(&calibration).unwrap();
+    assert_eq!(Calibration::from_device(&d), calibration);
+
+    let mut missing = Calibration::default();
+    for code in EventCodeIterator::new(&EventType::EV_ABS) {
+        if let EventCode::EV_ABS(abs) = code {
+            if !d.has(code) && abs != EV_ABS::ABS_MT_SLOT {
+                missing.axes.insert(
+                    abs,
+                    AbsInfo {
+                        value: 0,
+                        minimum: 0,
+                        maximum: 1,
+                        fuzz: 0,
+                        flat: 0,
+                        resolution: 0,
+                    },
+                );
+                break;
+            }
+        }
+    }
+    assert!(d.apply_calibration(&missing).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn device_save_load_calibration() {
+    let d = Device::new_from_path("/dev/input/event0").unwrap();
+    let mut json = Vec::new();
+    d.save_calibration(&mut json).unwrap();
+
+    let saved: Calibration = serde_json::from_slice(&json).unwrap();
+    assert_eq!(saved, Calibration::from_device(&d));
+    d.load_calibration(&mut json.as_slice()).unwrap();
+    assert_eq!(Calibration::from_device(&d), saved);
+
+    let err = d.load_calibration(&mut &b"{\"axes\": 1}"[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn device_try_next_event() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    assert!(d.try_next_event(ReadFlag::NORMAL).unwrap().is_none());
+
+    let time = TimeVal::new(0, 0);
+    keyboard
+        .write_events(&[
+            InputEvent::new(&time, &EventCode::EV_KEY(EV_KEY::KEY_A), 1),
+            InputEvent::new(&time, &EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0),
+        ])
+        .unwrap();
+
+    let (_, ev) = d.try_next_event(ReadFlag::NORMAL).unwrap().unwrap();
+    assert_eq!(ev.event_code, EventCode::EV_KEY(EV_KEY::KEY_A));
+}
+
+#[test]
+fn device_validate_event() {
+    let d = UninitDevice::new().unwrap();
+    let info = AbsInfo {
+        value: 0,
+        minimum: -10,
+        maximum: 10,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let x = EventCode::EV_ABS(EV_ABS::ABS_X);
+    let id = EventCode::EV_ABS(EV_ABS::ABS_MT_TRACKING_ID);
+    let key = EventCode::EV_KEY(EV_KEY::KEY_A);
+    d.enable_event_code(&x, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+    d.enable_event_code(
+        &id,
+        Some(EnableCodeData::AbsInfo(AbsInfo {
+            minimum: 0,
+            maximum: 0xffff,
+            ..info
+        })),
+    )
+    .unwrap();
+    d.enable(key).unwrap();
+
+    let time = TimeVal::new(0, 0);
+    let event = |code: &EventCode, value| InputEvent::new(&time, code, value);
+    assert!(d.validate_event(&event(&key, 1)).is_ok());
+    assert!(d.validate_event(&event(&x, -10)).is_ok());
+    assert!(d.validate_event(&event(&x, 10)).is_ok());
+    assert!(d.validate_event(&event(&id, -1)).is_ok());
+
+    let error = d.validate_event(&event(&x, 11)).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(d.validate_event(&event(&x, -11)).is_err());
+    assert!(d.validate_event(&event(&id, -2)).is_err());
+    assert!(d
+        .validate_event(&event(&EventCode::EV_KEY(EV_KEY::KEY_B), 1))
+        .is_err());
+}
+
+#[test]
+fn device_into_interceptor() {
+    let mouse = UInputDevice::virtual_mouse().unwrap();
+    let d = Device::new_from_path(mouse.devnode().unwrap()).unwrap();
+
+    let (d, uinput) = d.into_interceptor().unwrap();
+    let clone = Device::new_from_path(uinput.devnode().unwrap()).unwrap();
+    assert!(d.capabilities_match(&clone));
+    assert_eq!(d.name(), clone.name());
+
+    // Grabbed, so the event doesn't reach other clients
+    let other = Device::new_from_path(mouse.devnode().unwrap()).unwrap();
+    mouse.move_rel(1, 0).unwrap();
+    assert!(d.try_next_event(ReadFlag::NORMAL).unwrap().is_some());
+    assert!(other.try_next_event(ReadFlag::NORMAL).unwrap().is_none());
+}
+
+#[test]
+fn frames_type_a() {
+    let time = TimeVal::new(0, 0);
+    let ev = |code, value| InputEvent::new(&time, &code, value);
+    let x = EventCode::EV_ABS(EV_ABS::ABS_MT_POSITION_X);
+    let y = EventCode::EV_ABS(EV_ABS::ABS_MT_POSITION_Y);
+    let touch = EventCode::EV_KEY(EV_KEY::BTN_TOUCH);
+    let mt_report = EventCode::EV_SYN(EV_SYN::SYN_MT_REPORT);
+    let report = EventCode::EV_SYN(EV_SYN::SYN_REPORT);
+
+    let events = vec![
+        ev(touch, 1),
+        ev(x, 10),
+        ev(y, 20),
+        ev(mt_report, 0),
+        ev(x, 30),
+        ev(y, 40),
+        ev(mt_report, 0),
+        ev(report, 0),
+        ev(mt_report, 0),
+        ev(report, 0),
+        ev(touch, 0),
+    ];
+
+    let frames: Vec<_> = Frames::new(events.clone().into_iter()).collect();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0], &events[..8]);
+    assert_eq!(frames[1], &events[8..10]);
+    assert_eq!(frames[2], &events[10..]);
+
+    let contacts = mt_contacts(&frames[0]);
+    assert_eq!(contacts, vec![&events[..3], &events[4..6]]);
+
+    // A frame without contacts, all fingers lifted
+    assert_eq!(mt_contacts(&frames[1]), vec![&[] as &[InputEvent]]);
+    assert!(mt_contacts(&frames[2]).is_empty());
+}
+
+#[test]
+fn device_read_raw_batch() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let mut d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    assert_eq!(d.read_buffer(), 64);
+    d.with_read_buffer(0);
+    assert_eq!(d.read_buffer(), 1);
+    d.with_read_buffer(3);
+
+    let time = TimeVal::new(0, 0);
+    let syn = InputEvent::new(&time, &EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0);
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    keyboard
+        .write_events(&[
+            InputEvent::new(&time, &a, 1),
+            syn.clone(),
+            InputEvent::new(&time, &a, 0),
+            syn,
+        ])
+        .unwrap();
+
+    let first = d.read_raw_batch().unwrap();
+    assert_eq!(first.len(), 3);
+    assert_eq!((first[0].event_code, first[0].value), (a, 1));
+    let second = d.read_raw_batch().unwrap();
+    assert_eq!(second.len(), 1);
+    assert!(d.read_raw_batch().is_err());
+}
+
+#[test]
+fn device_rel_axes() {
+    let d = UninitDevice::new().unwrap();
+    assert!(d.rel_axes().is_empty());
+    assert!(!d.has_wheel());
+
+    for rel in &[EV_REL::REL_WHEEL, EV_REL::REL_Y, EV_REL::REL_X] {
+        d.enable(EventCode::EV_REL(*rel)).unwrap();
+    }
+    assert_eq!(
+        d.rel_axes(),
+        vec![EV_REL::REL_X, EV_REL::REL_Y, EV_REL::REL_WHEEL]
+    );
+    assert!(d.has_wheel());
+    assert!(!d.has_hi_res_wheel());
+
+    d.enable(EventCode::EV_REL(EV_REL::REL_WHEEL_HI_RES))
+        .unwrap();
+    assert!(d.has_hi_res_wheel());
+}
+
+#[test]
+fn device_on_sync() {
+    use std::sync::{Arc, Mutex};
+
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    let deltas = Arc::new(Mutex::new(Vec::new()));
+    let handler_deltas = deltas.clone();
+    d.on_sync(move |delta| handler_deltas.lock().unwrap().push(delta.to_vec()));
+
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    keyboard
+        .write_event(&InputEvent::new(&TimeVal::new(0, 0), &a, 1))
+        .unwrap();
+    keyboard
+        .write_event(&InputEvent::new(
+            &TimeVal::new(0, 0),
+            &EventCode::EV_SYN(EV_SYN::SYN_REPORT),
+            0,
+        ))
+        .unwrap();
+
+    let res = d.next_event(ReadFlag::NORMAL | ReadFlag::FORCE_SYNC);
+    assert!(res.is_err());
+    assert!(!d.sync_pending());
+
+    let deltas = deltas.lock().unwrap();
+    assert_eq!(deltas.len(), 1);
+    assert!(deltas[0]
+        .iter()
+        .any(|ev| ev.event_code == a && ev.value == 1));
+}
+
+#[test]
+fn device_mt_extents() {
+    let d = UninitDevice::new().unwrap();
+    assert_eq!(d.mt_extents(), None);
+
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 0,
+        flat: 0,
+        resolution: 10,
+    };
+    let enable = |abs, info| {
+        d.enable_event_code(&EventCode::EV_ABS(abs), Some(EnableCodeData::AbsInfo(info)))
+            .unwrap()
+    };
+    enable(EV_ABS::ABS_X, info);
+    enable(
+        EV_ABS::ABS_Y,
+        AbsInfo {
+            maximum: 500,
+            ..info
+        },
+    );
+    let extents = d.mt_extents().unwrap();
+    assert_eq!((extents.x_max, extents.y_max), (1000, 500));
+
+    enable(
+        EV_ABS::ABS_MT_POSITION_X,
+        AbsInfo {
+            maximum: 2000,
+            ..info
+        },
+    );
+    assert_eq!(d.mt_extents().unwrap().x_max, 1000);
+    enable(
+        EV_ABS::ABS_MT_POSITION_Y,
+        AbsInfo {
+            resolution: 20,
+            ..info
+        },
+    );
+    assert_eq!(
+        d.mt_extents(),
+        Some(MtExtents {
+            x_min: 0,
+            x_max: 2000,
+            x_resolution: 10,
+            y_min: 0,
+            y_max: 1000,
+            y_resolution: 20,
+        })
+    );
+}
+
+#[test]
+fn device_enable_all_codes() {
+    let d = UninitDevice::new().unwrap();
+    d.enable_all_codes(&EventType::EV_KEY).unwrap();
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::KEY_A)));
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::BTN_LEFT)));
+    assert!(!d.has(EventCode::EV_REL(EV_REL::REL_X)));
+
+    let err = d.enable_all_codes(&EventType::EV_ABS).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!d.has(EventType::EV_ABS));
+}
+
+#[test]
+fn key_state_values() {
+    for state in &[KeyState::Released, KeyState::Pressed, KeyState::Repeated] {
+        assert_eq!(KeyState::from_value(state.to_value()), Some(*state));
+    }
+    assert_eq!(KeyState::Repeated.to_value(), 2);
+    assert_eq!(KeyState::from_value(3), None);
+}
+
+#[test]
+fn uinput_key() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    keyboard.key(EV_KEY::KEY_A, KeyState::Pressed).unwrap();
+    keyboard.key(EV_KEY::KEY_A, KeyState::Released).unwrap();
+
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let mut states = Vec::new();
+    while d.has_event_pending() {
+        let (_, ev) = d.next_event(ReadFlag::NORMAL).unwrap();
+        if ev.event_code == a {
+            states.push(KeyState::from_value(ev.value).unwrap());
+        }
+    }
+    assert_eq!(states, vec![KeyState::Pressed, KeyState::Released]);
+}
+
+#[test]
+fn device_abs_value_mm() {
+    let d = UninitDevice::new().unwrap();
+    let x = EventCode::EV_ABS(EV_ABS::ABS_X);
+    let y = EventCode::EV_ABS(EV_ABS::ABS_Y);
+    assert_eq!(d.abs_value_mm(&x), None);
+
+    let info = AbsInfo {
+        value: 250,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 0,
+        flat: 0,
+        resolution: 10,
+    };
+    d.enable_event_code(&x, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+    d.enable_event_code(
+        &y,
+        Some(EnableCodeData::AbsInfo(AbsInfo {
+            resolution: 0,
+            ..info
+        })),
+    )
+    .unwrap();
+    assert_eq!(d.abs_value_mm(&x), Some(25.0));
+    assert_eq!(d.abs_value_mm(&y), None);
+}
+
+#[test]
+fn uinput_create_from_device_with_id() {
+    let mut u = UninitDevice::new().unwrap();
+    u.set_name("evdev-rs source");
+    u.set_vendor_id(0x1234);
+    u.enable(EventCode::EV_KEY(EV_KEY::KEY_A)).unwrap();
+
+    let id = DeviceId {
+        bustype: BusType::BUS_VIRTUAL,
+        vendor: 0x4321,
+        product: 0x0002,
+        version: 1,
+    };
+    let uinput =
+        UInputDevice::create_from_device_with_id(&u, &id, "evdev-rs split").unwrap();
+    let d = Device::new_from_path(uinput.devnode().unwrap()).unwrap();
+    assert_eq!(d.name(), Some("evdev-rs split"));
+    assert_eq!(d.vendor_id(), 0x4321);
+    assert_eq!(d.product_id(), 0x0002);
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::KEY_A)));
+    assert_eq!(u.vendor_id(), 0x1234);
+}
+
+#[test]
+fn device_description_diff() {
+    let info = AbsInfo {
+        value: 10,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let x = EventCode::EV_ABS(EV_ABS::ABS_X);
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let b = EventCode::EV_KEY(EV_KEY::KEY_B);
+
+    let old = UninitDevice::new().unwrap();
+    old.enable(a).unwrap();
+    old.enable_event_code(&x, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+
+    let new = UninitDevice::new().unwrap();
+    new.enable(b).unwrap();
+    new.enable_event_code(
+        &x,
+        Some(EnableCodeData::AbsInfo(AbsInfo { value: 20, ..info })),
+    )
+    .unwrap();
+    new.enable_property(&InputProp::INPUT_PROP_POINTER).unwrap();
+
+    let old_desc = DeviceDescription::from_device(&old);
+    assert!(old_desc.diff(&old_desc).is_empty());
+    assert!(old_desc
+        .capabilities
+        .has(&EventType::EV_KEY, EV_KEY::KEY_A as u32));
+    assert!(old_desc.capabilities.abs_info.is_empty());
+    assert_eq!(
+        old_desc.calibration.axes.get(&EV_ABS::ABS_X),
+        Some(&AbsInfo { value: 0, ..info })
+    );
+
+    // ABS_MT_SLOT can't be calibrated, it stays with the capabilities
+    let slot = EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT);
+    let mt = UninitDevice::new().unwrap();
+    mt.enable_event_code(&slot, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+    let mt_desc = DeviceDescription::from_device(&mt);
+    assert!(mt_desc.calibration.axes.is_empty());
+    assert_eq!(
+        mt_desc.capabilities.abs_info.get(&EV_ABS::ABS_MT_SLOT),
+        Some(&AbsInfo { value: 0, ..info })
+    );
+    mt.set_abs_info(&slot, &AbsInfo { maximum: 9, ..info });
+    let diff = mt_desc.diff(&DeviceDescription::from_device(&mt));
+    assert_eq!(diff.changed_abs_info.len(), 1);
+    let diff = old_desc.diff(&DeviceDescription::from_device(&new));
+    assert!(diff.changed_abs_info.is_empty());
+
+    new.set_abs_info(
+        &x,
+        &AbsInfo {
+            maximum: 2000,
+            ..info
+        },
+    );
+    let diff = old_desc.diff(&DeviceDescription::from_device(&new));
+    assert_eq!(diff.added_codes, vec![b]);
+    assert_eq!(diff.removed_codes, vec![a]);
+    assert_eq!(diff.added_properties, vec![InputProp::INPUT_PROP_POINTER]);
+    assert_eq!(
+        diff.changed_abs_info,
+        vec![(
+            EV_ABS::ABS_X,
+            AbsInfo { value: 0, ..info },
+            AbsInfo {
+                value: 0,
+                maximum: 2000,
+                ..info
+            }
+        )]
+    );
+}
+
+#[test]
+fn device_is_nonblocking() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let path = keyboard.devnode().unwrap();
+
+    let d = Device::new_from_path(path).unwrap();
+    assert!(d.is_nonblocking().unwrap());
+
+    let d = Device::new_from_file(File::open(path).unwrap()).unwrap();
+    assert!(!d.is_nonblocking().unwrap());
+}
+
+#[test]
+fn device_capability_bitmap() {
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 255,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let d = UninitDevice::new().unwrap();
+    d.enable(EventCode::EV_KEY(EV_KEY::KEY_A)).unwrap();
+    d.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT)).unwrap();
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_Z),
+        Some(EnableCodeData::AbsInfo(info)),
+    )
+    .unwrap();
+    d.enable_property(&InputProp::INPUT_PROP_DIRECT).unwrap();
+
+    let bitmap = d.capability_bitmap();
+    assert!(bitmap.has(&EventType::EV_KEY, EV_KEY::KEY_A as u32));
+    assert!(!bitmap.has(&EventType::EV_KEY, EV_KEY::KEY_B as u32));
+    assert_eq!(
+        bitmap.codes[&EventType::EV_ABS],
+        vec![1 << EV_ABS::ABS_Z as u8]
+    );
+    assert_eq!(
+        bitmap.properties,
+        vec![1 << InputProp::INPUT_PROP_DIRECT as u8]
+    );
+
+    let copy = UninitDevice::from_capability_bitmap(&bitmap).unwrap();
+    assert_eq!(copy.capability_bitmap(), bitmap);
+    assert_eq!(copy.abs_info(&EventCode::EV_ABS(EV_ABS::ABS_Z)), Some(info));
+
+    let mut missing = bitmap.clone();
+    missing.abs_info.clear();
+    assert!(UninitDevice::from_capability_bitmap(&missing).is_err());
+}
+
+#[test]
+fn throttled_reader() {
+    let mouse = UInputDevice::virtual_mouse().unwrap();
+    let d = Device::new_from_path(mouse.devnode().unwrap()).unwrap();
+    let mut reader = ThrottledReader::new(d, 2, ThrottlePolicy::Coalesce);
+
+    for _ in 0..5 {
+        mouse.move_rel(1, 0).unwrap();
+    }
+
+    let rel_x = EventCode::EV_REL(EV_REL::REL_X);
+    let syn = EventCode::EV_SYN(EV_SYN::SYN_REPORT);
+    let mut events = Vec::new();
+    while let Some(ev) = reader.next_event().unwrap() {
+        events.push((ev.event_code, ev.value));
+    }
+    assert_eq!(events, vec![(rel_x, 1), (syn, 0)]);
+    assert_eq!(reader.dropped(), 8);
+
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    let mut events = Vec::new();
+    while let Some(ev) = reader.next_event().unwrap() {
+        events.push((ev.event_code, ev.value));
+    }
+    assert_eq!(events, vec![(rel_x, 4), (syn, 0)]);
+
+    // The coalesced events used up the limit of this window
+    mouse.move_rel(1, 0).unwrap();
+    assert_eq!(reader.next_event().unwrap(), None);
+    assert_eq!(reader.dropped(), 10);
+}
+
+#[test]
+fn input_event_typed_constructors() {
+    let time = TimeVal::new(0, 0);
+    assert_eq!(
+        InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed),
+        InputEvent::new(&time, &EventCode::EV_KEY(EV_KEY::KEY_A), 1)
+    );
+    assert_eq!(
+        InputEvent::rel(EV_REL::REL_X, -3),
+        InputEvent::new(&time, &EventCode::EV_REL(EV_REL::REL_X), -3)
+    );
+    assert_eq!(
+        InputEvent::abs(EV_ABS::ABS_Y, 512),
+        InputEvent::new(&time, &EventCode::EV_ABS(EV_ABS::ABS_Y), 512)
+    );
+    assert_eq!(
+        InputEvent::syn_report(),
+        InputEvent::new(&time, &EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0)
+    );
+}
+
+#[test]
+fn device_needs_resync() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let path = keyboard.devnode().unwrap();
+    let mut d = Device::new_from_path(path).unwrap();
+    assert!(!d.needs_resync());
+
+    d.change_file(File::open(path).unwrap()).unwrap();
+    assert!(d.needs_resync());
+
+    let (status, _) = d.next_event(ReadFlag::FORCE_SYNC).unwrap();
+    assert!(status == ReadStatus::Sync);
+    assert!(!d.needs_resync());
+}
+
+#[test]
+fn device_ff_effect_unsupported() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    let mut effect = FfEffect {
+        kind: FfEffectKind::Periodic {
+            waveform: EV_FF::FF_RUMBLE,
+            period: 100,
+            magnitude: 0x4000,
+            offset: 0,
+            phase: 0,
+            envelope: FfEnvelope::default(),
+        },
+        direction: 0,
+        trigger: FfTrigger::default(),
+        replay: FfReplay {
+            length: 200,
+            delay: 0,
+        },
+    };
+    let err = d.upload_ff_effect(&effect).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    // The keyboard doesn't support force feedback at all
+    effect.kind = FfEffectKind::Rumble {
+        strong_magnitude: 0x8000,
+        weak_magnitude: 0x8000,
+    };
+    assert!(d.upload_ff_effect(&effect).is_err());
+    assert!(d.erase_ff_effect(0).is_err());
+}
+
+#[test]
+fn device_current_state() {
+    let d = UninitDevice::new().unwrap();
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let b = EventCode::EV_KEY(EV_KEY::KEY_B);
+    let x = EventCode::EV_REL(EV_REL::REL_X);
+    for code in &[x, b, a] {
+        d.enable(*code).unwrap();
+    }
+    d.set_event_value(&a, 1).unwrap();
+
+    let state = d.current_state();
+    assert!(state.contains(&(a, 1)));
+    assert!(state.contains(&(b, 0)));
+    assert!(state.contains(&(x, 0)));
+    let a_pos = state.iter().position(|(code, _)| *code == a).unwrap();
+    let x_pos = state.iter().position(|(code, _)| *code == x).unwrap();
+    assert!(a_pos < x_pos);
+
+    let keys = d.current_state_filtered(|code, _| matches!(code, EventCode::EV_KEY(_)));
+    assert_eq!(keys, vec![(a, 1), (b, 0)]);
+}
+
+#[test]
+fn device_resync_and_report_changes() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    keyboard.key(EV_KEY::KEY_A, KeyState::Pressed).unwrap();
+
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let delta = d.resync_and_report_changes().unwrap();
+    assert!(delta.iter().any(|ev| ev.event_code == a && ev.value == 1));
+    assert_eq!(d.event_value(&a), Some(1));
+    assert!(!d.sync_pending());
+    assert!(d
+        .resync_and_report_changes()
+        .unwrap()
+        .iter()
+        .all(|ev| ev.is_type(&EventType::EV_SYN)));
+}
+
+#[test]
+fn enum_all_names() {
+    let keys: Vec<_> = EV_KEY::all_names().collect();
+    assert!(keys.contains(&(EV_KEY::KEY_A, "KEY_A")));
+    assert!(keys.contains(&(EV_KEY::BTN_LEFT, "BTN_LEFT")));
+    assert!(keys.windows(2).all(|w| (w[0].0 as u32) < (w[1].0 as u32)));
+
+    assert_eq!(EV_REL::all_names().next(), Some((EV_REL::REL_X, "REL_X")));
+    assert!(EV_ABS::all_names()
+        .any(|(abs, name)| abs == EV_ABS::ABS_MT_SLOT && name == "ABS_MT_SLOT"));
+    assert_eq!(
+        InputProp::all_names().next(),
+        Some((InputProp::INPUT_PROP_POINTER, "INPUT_PROP_POINTER"))
+    );
+}
+
+#[test]
+fn device_reset_to_kernel() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let mut d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let x = EventCode::EV_REL(EV_REL::REL_X);
+
+    d.set_event_value(&a, 1).unwrap();
+    d.enable(x).unwrap();
+    d.disable(EventCode::EV_KEY(EV_KEY::KEY_B)).unwrap();
+    d.grab(GrabMode::Grab).unwrap();
+
+    d.reset_to_kernel().unwrap();
+    assert_eq!(d.event_value(&a), Some(0));
+    assert!(!d.has(x));
+    assert!(d.has(EventCode::EV_KEY(EV_KEY::KEY_B)));
+    d.grab(GrabMode::Ungrab).unwrap();
+}
+
+#[test]
+fn uinput_scroll() {
+    let mut u = UninitDevice::new().unwrap();
+    u.set_name("evdev-rs hi-res wheel");
+    u.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT)).unwrap();
+    u.enable(EventCode::EV_REL(EV_REL::REL_WHEEL)).unwrap();
+    u.enable(EventCode::EV_REL(EV_REL::REL_WHEEL_HI_RES))
+        .unwrap();
+    let wheel = UInputDevice::create_from_device(&u).unwrap();
+    let mouse = UInputDevice::virtual_mouse().unwrap();
+
+    let read_all = |d: &Device| {
+        let mut events = Vec::new();
+        while d.has_event_pending() {
+            let (_, ev) = d.next_event(ReadFlag::NORMAL).unwrap();
+            events.push((ev.event_code, ev.value));
+        }
+        events
+    };
+    let rel = |axis| EventCode::EV_REL(axis);
+    let syn = (EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0);
+
+    let d = Device::new_from_path(wheel.devnode().unwrap()).unwrap();
+    wheel.scroll(0).unwrap();
+    wheel.scroll(-2).unwrap();
+    assert_eq!(
+        read_all(&d),
+        vec![
+            (rel(EV_REL::REL_WHEEL), -2),
+            (rel(EV_REL::REL_WHEEL_HI_RES), -240),
+            syn
+        ]
+    );
+
+    let d = Device::new_from_path(mouse.devnode().unwrap()).unwrap();
+    mouse.scroll(1).unwrap();
+    assert_eq!(read_all(&d), vec![(rel(EV_REL::REL_WHEEL), 1), syn]);
+}
+
+#[test]
+fn device_last_read_info() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    assert_eq!(d.last_read_info(), None);
+
+    assert!(d.next_event(ReadFlag::NORMAL).is_err());
+    assert_eq!(
+        d.last_read_info(),
+        Some(ReadInfo {
+            flags: ReadFlag::NORMAL,
+            status: None,
+            errno: Some(libc::EAGAIN),
+        })
+    );
+
+    keyboard.key(EV_KEY::KEY_A, KeyState::Pressed).unwrap();
+    d.next_event(ReadFlag::NORMAL).unwrap();
+    let info = d.last_read_info().unwrap();
+    assert_eq!(info.status, Some(ReadStatus::Success));
+    assert_eq!(info.errno, None);
+}
+
+#[test]
+fn time_val_now() {
+    use std::convert::TryFrom;
+
+    let first = TimeVal::now_monotonic().unwrap();
+    let second = TimeVal::now_monotonic().unwrap();
+    assert!(second >= first);
+    assert!(first.tv_usec < 1_000_000);
+
+    let realtime = TimeVal::now_realtime().unwrap();
+    let system = TimeVal::try_from(std::time::SystemTime::now()).unwrap();
+    assert!((system.tv_sec - realtime.tv_sec).abs() <= 1);
+}
+
+#[test]
+fn device_properties() {
+    let d = UninitDevice::new().unwrap();
+    assert!(d.properties().is_empty());
+
+    d.enable_property(&InputProp::INPUT_PROP_BUTTONPAD).unwrap();
+    d.enable_property(&InputProp::INPUT_PROP_POINTER).unwrap();
+    assert_eq!(
+        d.properties(),
+        vec![
+            InputProp::INPUT_PROP_POINTER,
+            InputProp::INPUT_PROP_BUTTONPAD
+        ]
+    );
+}
+
+#[test]
+fn event_filter() {
+    let events = vec![
+        InputEvent::rel(EV_REL::REL_X, 1),
+        InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed),
+        InputEvent::syn_report(),
+        InputEvent::key(EV_KEY::KEY_B, KeyState::Pressed),
+        InputEvent::key(EV_KEY::KEY_A, KeyState::Released),
+        InputEvent::syn_report(),
+    ];
+
+    let keys: Vec<_> = events
+        .iter()
+        .cloned()
+        .only_type(EventType::EV_KEY)
+        .map(|ev| ev.event_code)
+        .collect();
+    assert_eq!(keys.len(), 3);
+
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let values: Vec<_> = events
+        .into_iter()
+        .only_type(EventType::EV_KEY)
+        .only_code(a)
+        .map(|ev| ev.value)
+        .collect();
+    assert_eq!(values, vec![1, 0]);
+}
+
+#[test]
+fn input_event_hw_timestamp() {
+    let time = TimeVal::new(0, 0);
+    let ts = InputEvent::new(&time, &EventCode::EV_MSC(EV_MSC::MSC_TIMESTAMP), 12345);
+    assert_eq!(ts.hw_timestamp_us(), Some(12345));
+
+    let scan = InputEvent::new(&time, &EventCode::EV_MSC(EV_MSC::MSC_SCAN), 12345);
+    assert_eq!(scan.hw_timestamp_us(), None);
+    assert_eq!(InputEvent::syn_report().hw_timestamp_us(), None);
+}
+
+#[test]
+fn device_open_matching() {
+    let mut u = UninitDevice::new().unwrap();
+    u.set_name("evdev-rs open matching");
+    u.set_bustype(BusType::BUS_USB as u16);
+    u.set_vendor_id(0x1234);
+    u.set_product_id(0x5678);
+    u.set_version(1);
+    u.enable(EventCode::EV_KEY(EV_KEY::KEY_A)).unwrap();
+    let uinput = UInputDevice::create_from_device(&u).unwrap();
+    let path = uinput.devnode().unwrap();
+
+    let mut id = DeviceId {
+        bustype: BusType::BUS_USB,
+        vendor: 0x1234,
+        product: 0x5678,
+        version: 1,
+    };
+    let d = Device::open_matching(path, &id).unwrap();
+    assert_eq!(d.name(), Some("evdev-rs open matching"));
+
+    id.product = 0x5679;
+    let err = Device::open_matching(path, &id).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn device_active_touch_count() {
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 2,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+
+    let type_a = UninitDevice::new().unwrap();
+    assert_eq!(type_a.active_touch_count(), 0);
+    for key in &[
+        EV_KEY::BTN_TOUCH,
+        EV_KEY::BTN_TOOL_FINGER,
+        EV_KEY::BTN_TOOL_TRIPLETAP,
+    ] {
+        type_a.enable(EventCode::EV_KEY(*key)).unwrap();
+    }
+    type_a
+        .set_event_value(&EventCode::EV_KEY(EV_KEY::BTN_TOUCH), 1)
+        .unwrap();
+    assert_eq!(type_a.active_touch_count(), 1);
+    type_a
+        .set_event_value(&EventCode::EV_KEY(EV_KEY::BTN_TOOL_TRIPLETAP), 1)
+        .unwrap();
+    assert_eq!(type_a.active_touch_count(), 3);
+
+    let type_b = UninitDevice::new().unwrap();
+    let id = EventCode::EV_ABS(EV_ABS::ABS_MT_TRACKING_ID);
+    type_b
+        .enable_event_code(
+            &EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT),
+            Some(EnableCodeData::AbsInfo(info)),
+        )
+        .unwrap();
+    type_b
+        .enable_event_code(
+            &id,
+            Some(EnableCodeData::AbsInfo(AbsInfo {
+                minimum: -1,
+                maximum: 0xffff,
+                ..info
+            })),
+        )
+        .unwrap();
+    for slot in 0..3 {
+        type_b.set_slot_value(slot, &id, -1).unwrap();
+    }
+    assert_eq!(type_b.active_touch_count(), 0);
+    type_b.set_slot_value(0, &id, 7).unwrap();
+    type_b.set_slot_value(2, &id, 8).unwrap();
+    assert_eq!(type_b.active_touch_count(), 2);
+}
+
+#[test]
+fn evdev_version() {
+    let version = EvdevVersion::from_raw(0x010001);
+    assert_eq!(
+        version,
+        EvdevVersion {
+            major: 1,
+            minor: 0,
+            patch: 1
+        }
+    );
+    assert_eq!(version.to_raw(), 0x010001);
+    assert_eq!(version.to_string(), "1.0.1");
+    assert!(EvdevVersion::from_raw(0x010000) < version);
+    assert!(version < EvdevVersion::from_raw(0x010100));
+}
+
+#[test]
+fn device_read_raw_batch_after_next_event() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let mut d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    keyboard
+        .write_events(&[
+            InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed),
+            InputEvent::syn_report(),
+        ])
+        .unwrap();
+
+    let (_, ev) = d.next_event(ReadFlag::NORMAL).unwrap();
+    assert_eq!((ev.event_code, ev.value), (a, 1));
+    let err = d.read_raw_batch().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+
+    while d.next_event(ReadFlag::NORMAL).is_ok() {}
+    keyboard
+        .write_events(&[
+            InputEvent::key(EV_KEY::KEY_A, KeyState::Released),
+            InputEvent::syn_report(),
+        ])
+        .unwrap();
+    let events = d.read_raw_batch().unwrap();
+    assert_eq!((events[0].event_code, events[0].value), (a, 0));
+}
+
+#[test]
+fn event_code_from_name() {
+    assert_eq!(
+        EventCode::from_name("KEY_CAPSLOCK"),
+        Some(EventCode::EV_KEY(EV_KEY::KEY_CAPSLOCK))
+    );
+    assert_eq!(
+        EventCode::from_name("BTN_LEFT"),
+        Some(EventCode::EV_KEY(EV_KEY::BTN_LEFT))
+    );
+    assert_eq!(
+        EventCode::from_name("ABS_MT_SLOT"),
+        Some(EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT))
+    );
+    assert_eq!(EventCode::from_name("KEY_NOPE"), None);
+    assert_eq!(EventCode::from_name("EV_KEY"), None);
+    assert_eq!(EventCode::from_name("KEYA"), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn remap_config() {
+    let caps = EventCode::EV_KEY(EV_KEY::KEY_CAPSLOCK);
+    let ctrl = EventCode::EV_KEY(EV_KEY::KEY_LEFTCTRL);
+    let mut config = RemapConfig::default();
+    assert_eq!(config.insert(caps, ctrl), None);
+    assert_eq!(config.get(&caps), Some(ctrl));
+    assert_eq!(config.get(&ctrl), None);
+
+    let ev = InputEvent::key(EV_KEY::KEY_CAPSLOCK, KeyState::Pressed);
+    assert_eq!(
+        (config.remap(&ev).event_code, config.remap(&ev).value),
+        (ctrl, 1)
+    );
+    let ev = InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed);
+    assert_eq!(config.remap(&ev), ev);
+
+    let json = r#"{"KEY_CAPSLOCK":"KEY_LEFTCTRL"}"#;
+    let parsed: RemapConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(parsed, config);
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+    let error = serde_json::from_str::<RemapConfig>(r#"{"KEY_NOPE":"KEY_LEFTCTRL"}"#)
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("unknown event code \"KEY_NOPE\""));
+}
+
+#[test]
+fn device_with_each_slot() {
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 2,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let slot = EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT);
+
+    let d = UninitDevice::new().unwrap();
+    let mut visited = Vec::new();
+    d.with_each_slot(|slot, _| visited.push(slot));
+    assert!(visited.is_empty());
+
+    d.enable_event_code(&slot, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+    d.set_event_value(&slot, 1).unwrap();
+    d.with_each_slot(|s, d| {
+        d.set_event_value(&slot, s as i32).unwrap();
+        visited.push(s);
+    });
+    assert_eq!(visited, vec![0, 1, 2]);
+    assert_eq!(d.current_slot(), Some(1));
+}
+
+#[test]
+fn device_evtest_summary() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    let summary = d.evtest_summary();
+    let lines: Vec<&str> = summary.lines().collect();
+    assert!(lines[0].starts_with("Input device ID: bus 0x"));
+    assert_eq!(
+        lines[2],
+        format!("Input device name: \"{}\"", d.display_name())
+    );
+    assert!(lines.contains(&"Supported events:"));
+    assert!(lines.contains(&"  Event type: EV_KEY "));
+    assert!(lines.contains(&"    Event code: KEY_A"));
+    assert_eq!(lines.last(), Some(&"Properties:"));
+}
+
+#[test]
+fn abs_info_rescale() {
+    let info = AbsInfo {
+        value: 250,
+        minimum: -100,
+        maximum: 1000,
+        fuzz: 3,
+        flat: 0,
+        resolution: 12,
+    };
+    let scaled = info.rescale(4);
+    assert_eq!(
+        scaled,
+        AbsInfo {
+            value: 83,
+            minimum: -33,
+            maximum: 333,
+            fuzz: 1,
+            flat: 0,
+            resolution: 4,
+        }
+    );
+    assert_eq!(info.rescale(0), info);
+    assert_eq!(
+        AbsInfo {
+            resolution: 0,
+            ..info
+        }
+        .rescale(4)
+        .resolution,
+        0
+    );
+
+    assert_eq!(info.scale_value(-100, &scaled), -33);
+    assert_eq!(info.scale_value(1000, &scaled), 333);
+    assert_eq!(info.scale_value(2100, &scaled), 699);
+    let empty = AbsInfo {
+        minimum: 5,
+        maximum: 5,
+        ..info
+    };
+    assert_eq!(empty.scale_value(5, &scaled), -33);
+    let full = AbsInfo {
+        minimum: i32::MIN,
+        maximum: i32::MAX,
+        ..info
+    };
+    assert_eq!(full.scale_value(i32::MAX, &full), i32::MAX);
+    assert_eq!(info.scale_value(1000, &full), i32::MAX);
+}
+
+#[test]
+fn event_filter_skip_syn() {
+    let time = TimeVal::new(0, 0);
+    let dropped = InputEvent::new(&time, &EventCode::EV_SYN(EV_SYN::SYN_DROPPED), 0);
+    let events = vec![
+        InputEvent::rel(EV_REL::REL_X, 1),
+        InputEvent::syn_report(),
+        dropped.clone(),
+        InputEvent::rel(EV_REL::REL_X, 2),
+        InputEvent::syn_report(),
+    ];
+
+    let codes: Vec<_> = events
+        .iter()
+        .cloned()
+        .skip_syn()
+        .map(|ev| ev.event_code)
+        .collect();
+    assert_eq!(codes.len(), 3);
+    assert_eq!(codes[1], dropped.event_code);
+
+    let values: Vec<_> = events
+        .into_iter()
+        .skip_all_syn()
+        .map(|ev| ev.value)
+        .collect();
+    assert_eq!(values, vec![1, 2]);
+}
+
+#[test]
+fn device_autocenter() {
+    let d = UninitDevice::new().unwrap();
+    assert!(!d.has_autocenter());
+    d.enable(EventCode::EV_FF(EV_FF::FF_AUTOCENTER)).unwrap();
+    assert!(d.has_autocenter());
+
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    assert!(!d.has_autocenter());
+    let err = d.set_autocenter(0x8000).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOTSUP));
+}
+
+#[test]
+fn device_pressed_keys_at_grab() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let mut d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    keyboard
+        .write_events(&[
+            InputEvent::key(EV_KEY::KEY_LEFTSHIFT, KeyState::Pressed),
+            InputEvent::syn_report(),
+        ])
+        .unwrap();
+
+    assert!(d.pressed_keys_at_grab().is_empty());
+    d.grab(GrabMode::Grab).unwrap();
+    assert_eq!(d.pressed_keys_at_grab(), vec![EV_KEY::KEY_LEFTSHIFT]);
+
+    d.release_stuck_keys(&keyboard).unwrap();
+    d.grab(GrabMode::Ungrab).unwrap();
+    assert!(d.pressed_keys_at_grab().is_empty());
+}
+
+#[test]
+fn device_dispatch() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    keyboard
+        .write_events(&[
+            InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed),
+            InputEvent::syn_report(),
+        ])
+        .unwrap();
+
+    let mut events = Vec::new();
+    let err = d
+        .dispatch(ReadFlag::NORMAL, |ev| {
+            events.push((ev.event_code, ev.value))
+        })
+        .unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EAGAIN));
+    assert_eq!(
+        events,
+        vec![
+            (EventCode::EV_KEY(EV_KEY::KEY_A), 1),
+            (EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0)
+        ]
+    );
+}
+
+#[test]
+fn device_max_slot() {
+    let d = UninitDevice::new().unwrap();
+    assert_eq!(d.max_slot(), None);
+
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT),
+        Some(EnableCodeData::AbsInfo(AbsInfo {
+            value: 0,
+            minimum: 0,
+            maximum: 9,
+            fuzz: 0,
+            flat: 0,
+            resolution: 0,
+        })),
+    )
+    .unwrap();
+    assert_eq!(d.max_slot(), Some(9));
+}
+
+#[test]
+fn device_poll_event() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    assert!(matches!(
+        d.poll_event(ReadFlag::NORMAL),
+        NextEvent::WouldBlock
+    ));
+
+    keyboard
+        .write_events(&[
+            InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed),
+            InputEvent::syn_report(),
+        ])
+        .unwrap();
+    match d.poll_event(ReadFlag::NORMAL) {
+        NextEvent::Event(ev) => {
+            assert_eq!(ev.event_code, EventCode::EV_KEY(EV_KEY::KEY_A))
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(
+        d.poll_event(ReadFlag::NORMAL),
+        NextEvent::Event(_)
+    ));
+    assert!(matches!(
+        d.poll_event(ReadFlag::NORMAL),
+        NextEvent::WouldBlock
+    ));
+}
+
+#[test]
+fn uinput_set_initial_state() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let mut state = DeviceState::from_device(&d);
+    assert_eq!(state.value(&a), Some(0));
+    state.set_value(&a, 1);
+    keyboard.set_initial_state(&state).unwrap();
+
+    while d.next_event(ReadFlag::NORMAL).is_ok() {}
+    assert_eq!(d.event_value(&a), Some(1));
+    assert_eq!(DeviceState::from_device(&d), state);
+}
+
+#[test]
+fn input_event_scroll_delta() {
+    let delta = |axis, value| InputEvent::rel(axis, value).scroll_delta();
+    assert_eq!(
+        delta(EV_REL::REL_WHEEL, -2),
+        Some(ScrollDelta {
+            axis: ScrollAxis::Vertical,
+            clicks: -2.0
+        })
+    );
+    assert_eq!(
+        delta(EV_REL::REL_HWHEEL_HI_RES, 30),
+        Some(ScrollDelta {
+            axis: ScrollAxis::Horizontal,
+            clicks: 0.25
+        })
+    );
+    assert_eq!(delta(EV_REL::REL_WHEEL_HI_RES, 120).unwrap().clicks, 1.0);
+    assert_eq!(delta(EV_REL::REL_X, 1), None);
+    assert_eq!(InputEvent::syn_report().scroll_delta(), None);
+}
+
+#[test]
+fn device_physical_size_mm() {
+    let axis = |maximum, resolution| {
+        Some(EnableCodeData::AbsInfo(AbsInfo {
+            value: 0,
+            minimum: 0,
+            maximum,
+            fuzz: 0,
+            flat: 0,
+            resolution,
+        }))
+    };
+
+    let d = UninitDevice::new().unwrap();
+    assert_eq!(d.physical_size_mm(), None);
+
+    let code = |abs| EventCode::EV_ABS(abs);
+    d.enable_event_code(&code(EV_ABS::ABS_X), axis(1000, 0))
+        .unwrap();
+    d.enable_event_code(&code(EV_ABS::ABS_Y), axis(500, 0))
+        .unwrap();
+    assert_eq!(d.physical_size_mm(), None);
+
+    d.enable_event_code(&code(EV_ABS::ABS_X), axis(1000, 20))
+        .unwrap();
+    d.enable_event_code(&code(EV_ABS::ABS_Y), axis(500, 20))
+        .unwrap();
+    assert_eq!(d.physical_size_mm(), Some((50.0, 25.0)));
+
+    // The MT axes take precedence, like in mt_extents
+    d.enable_event_code(&code(EV_ABS::ABS_MT_POSITION_X), axis(3000, 30))
+        .unwrap();
+    d.enable_event_code(&code(EV_ABS::ABS_MT_POSITION_Y), axis(1500, 30))
+        .unwrap();
+    assert_eq!(d.physical_size_mm(), Some((100.0, 50.0)));
+    assert_eq!(d.mt_extents().unwrap().x_max, 3000);
+
+    d.enable_event_code(&code(EV_ABS::ABS_MT_POSITION_X), axis(3000, 0))
+        .unwrap();
+    assert_eq!(d.physical_size_mm(), None);
+}
+
+#[test]
+fn device_changes_since_snapshot() {
+    let d = UninitDevice::new().unwrap();
+    let a = EventCode::EV_KEY(EV_KEY::KEY_A);
+    let slot = EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT);
+    let x = EventCode::EV_ABS(EV_ABS::ABS_MT_POSITION_X);
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 1,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    d.enable(a).unwrap();
+    d.enable_event_code(&slot, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+    d.enable_event_code(
+        &x,
+        Some(EnableCodeData::AbsInfo(AbsInfo {
+            maximum: 100,
+            ..info
+        })),
+    )
+    .unwrap();
+
+    let snapshot = DeviceState::from_device(&d);
+    assert!(d.changes_since_snapshot(&snapshot).is_empty());
+
+    d.set_event_value(&a, 1).unwrap();
+    d.set_slot_value(1, &x, 50).unwrap();
+    let changes: Vec<_> = d
+        .changes_since_snapshot(&snapshot)
+        .iter()
+        .map(|ev| (ev.event_code, ev.value))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (a, 1),
+            (slot, 1),
+            (x, 50),
+            (slot, 0),
+            (EventCode::EV_SYN(EV_SYN::SYN_REPORT), 0)
+        ]
+    );
+}
+
+#[test]
+fn device_open_with_clock() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d =
+        Device::open_with_clock(keyboard.devnode().unwrap(), ClockId::Monotonic).unwrap();
+
+    let before = TimeVal::now_monotonic().unwrap();
+    keyboard.key(EV_KEY::KEY_A, KeyState::Pressed).unwrap();
+    let (_, ev) = d.next_event(ReadFlag::NORMAL).unwrap();
+    let after = TimeVal::now_monotonic().unwrap();
+    assert!(before <= ev.time && ev.time <= after);
+}
+
+#[test]
+fn uinput_write_abs() {
+    let info = AbsInfo {
+        value: 0,
+        minimum: -100,
+        maximum: 100,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let x = EventCode::EV_ABS(EV_ABS::ABS_X);
+    let mut u = UninitDevice::new().unwrap();
+    u.set_name("evdev-rs abs");
+    u.enable_event_code(&x, Some(EnableCodeData::AbsInfo(info)))
+        .unwrap();
+    let uinput = UInputDevice::create_from_device(&u).unwrap();
+    let d = Device::new_from_path(uinput.devnode().unwrap()).unwrap();
+
+    uinput.write_abs(EV_ABS::ABS_X, 500, &info).unwrap();
+    let (_, ev) = d.next_event(ReadFlag::NORMAL).unwrap();
+    assert_eq!((ev.event_code, ev.value), (x, 100));
+    uinput.write_abs(EV_ABS::ABS_X, -500, &info).unwrap();
+    while d.next_event(ReadFlag::NORMAL).is_ok() {}
+    assert_eq!(d.event_value(&x), Some(-100));
+
+    let inverted = AbsInfo {
+        minimum: 1,
+        maximum: 0,
+        ..info
+    };
+    let err = uinput.write_abs(EV_ABS::ABS_X, 0, &inverted).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn device_abs_live() {
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 255,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let mut u = UninitDevice::new().unwrap();
+    u.set_name("evdev-rs abs live");
+    for axis in &[EV_ABS::ABS_Y, EV_ABS::ABS_X] {
+        u.enable_event_code(
+            &EventCode::EV_ABS(*axis),
+            Some(EnableCodeData::AbsInfo(info)),
+        )
+        .unwrap();
+    }
+    let uinput = UInputDevice::create_from_device(&u).unwrap();
+    let d = Device::new_from_path(uinput.devnode().unwrap()).unwrap();
+
+    uinput.write_abs(EV_ABS::ABS_Y, 42, &info).unwrap();
+    while d.next_event(ReadFlag::NORMAL).is_ok() {}
+
+    let axes: Vec<_> = d.abs_live().collect();
+    assert_eq!(
+        axes,
+        vec![
+            (EV_ABS::ABS_X, 0, info),
+            (EV_ABS::ABS_Y, 42, AbsInfo { value: 42, ..info })
+        ]
+    );
+}
+
+#[test]
+fn device_keyboard_layout_hint() {
+    let d = UninitDevice::new().unwrap();
+    assert_eq!(d.keyboard_layout_hint(), None);
+
+    for code in DeviceClass::Keyboard.required_codes() {
+        d.enable(code).unwrap();
+    }
+    assert_eq!(d.keyboard_layout_hint(), Some(KeyboardLayout::Ansi));
+    d.enable(EventCode::EV_KEY(EV_KEY::KEY_102ND)).unwrap();
+    assert_eq!(d.keyboard_layout_hint(), Some(KeyboardLayout::Iso));
+    d.enable(EventCode::EV_KEY(EV_KEY::KEY_YEN)).unwrap();
+    assert_eq!(d.keyboard_layout_hint(), Some(KeyboardLayout::Jis));
+}
+
+#[test]
+fn device_split() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    let (reader, control) = d.split().unwrap();
+
+    let control = std::sync::Arc::new(control);
+    let grabber = std::sync::Arc::clone(&control);
+    std::thread::spawn(move || grabber.grab(GrabMode::Grab))
+        .join()
+        .unwrap()
+        .unwrap();
+    assert!(control.is_grabbed());
+
+    let press = InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed);
+    keyboard
+        .write_events(&[press.clone(), InputEvent::syn_report()])
+        .unwrap();
+    let (_, ev) = reader
+        .next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING)
+        .unwrap();
+    assert_eq!(ev.event_code, press.event_code);
+
+    control.grab(GrabMode::Ungrab).unwrap();
+    assert!(!control.is_grabbed());
+    let control = std::sync::Arc::try_unwrap(control).unwrap();
+    let mut d = reader.into_inner(control).unwrap();
+    d.grab(GrabMode::Grab).unwrap();
+    d.grab(GrabMode::Ungrab).unwrap();
+
+    // Grabbed before the split, ungrabbed through the control
+    d.grab(GrabMode::Grab).unwrap();
+    let (reader, control) = d.split().unwrap();
+    assert!(control.is_grabbed());
+    control.grab(GrabMode::Ungrab).unwrap();
+    let mut d = reader.into_inner(control).unwrap();
+    assert!(d.pressed_keys_at_grab().is_empty());
+    d.grab(GrabMode::Grab).unwrap();
+    d.grab(GrabMode::Ungrab).unwrap();
+
+    // Grabbed through the control, handed over to the device
+    let (reader, control) = d.split().unwrap();
+    control.grab(GrabMode::Grab).unwrap();
+    let d = reader.into_inner(control).unwrap();
+    let mut other = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    let err = other.grab(GrabMode::Grab).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+    d.close().unwrap();
+    other.grab(GrabMode::Grab).unwrap();
+}
+
+#[test]
+fn device_is_empty() {
+    let d = UninitDevice::new().unwrap();
+    assert!(d.is_empty());
+    assert!(d.has_event_type(&EventType::EV_SYN));
+    assert_eq!(
+        d.codes_by_type().keys().collect::<Vec<_>>(),
+        vec![&EventType::EV_SYN]
+    );
+    let err = UInputDevice::create_from_device(&d).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    d.enable(EventCode::EV_SYN(EV_SYN::SYN_REPORT)).unwrap();
+    assert!(d.is_empty());
+
+    d.enable(EventCode::EV_KEY(EV_KEY::KEY_A)).unwrap();
+    assert!(!d.is_empty());
+    UInputDevice::create_from_device(&d).unwrap();
+}
+
+#[test]
+fn device_mt_matrix() {
+    let d = UninitDevice::new().unwrap();
+    assert_eq!(d.mt_matrix(), None);
+
+    let info = AbsInfo {
+        value: 0,
+        minimum: 0,
+        maximum: 1000,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_MT_SLOT),
+        Some(EnableCodeData::AbsInfo(AbsInfo { maximum: 1, ..info })),
+    )
+    .unwrap();
+    // ABS_MAX is above the ABS_MT_* codes and not a column
+    d.enable_event_code(
+        &EventCode::EV_ABS(EV_ABS::ABS_MAX),
+        Some(EnableCodeData::AbsInfo(info)),
+    )
+    .unwrap();
+
+    let matrix = d.mt_matrix().unwrap();
+    assert!(matrix.codes().is_empty());
+    assert_eq!(matrix.num_slots(), 2);
+    assert_eq!(matrix.row(1), Some(&[][..]));
+    assert_eq!(matrix.row(2), None);
+
+    for abs in &[EV_ABS::ABS_MT_POSITION_Y, EV_ABS::ABS_MT_POSITION_X] {
+        d.enable_event_code(
+            &EventCode::EV_ABS(*abs),
+            Some(EnableCodeData::AbsInfo(info)),
+        )
+        .unwrap();
+    }
+    d.set_slot_value(1, &EventCode::EV_ABS(EV_ABS::ABS_MT_POSITION_Y), 20)
+        .unwrap();
+
+    let matrix = d.mt_matrix().unwrap();
+    assert_eq!(
+        matrix.codes(),
+        &[EV_ABS::ABS_MT_POSITION_X, EV_ABS::ABS_MT_POSITION_Y]
+    );
+    assert_eq!(matrix.num_slots(), 2);
+    assert_eq!(matrix.row(1), Some(&[0, 20][..]));
+    assert_eq!(matrix.value(1, EV_ABS::ABS_MT_POSITION_Y), Some(20));
+    assert_eq!(matrix.value(2, EV_ABS::ABS_MT_POSITION_Y), None);
+}
+
+#[test]
+fn merged_reader() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let mouse = UInputDevice::virtual_mouse().unwrap();
+    let mut reader = MergedReader::new();
+    let keyboard_token =
+        reader.add(Device::new_from_path(keyboard.devnode().unwrap()).unwrap());
+    let mouse_token =
+        reader.add(Device::new_from_path(mouse.devnode().unwrap()).unwrap());
+
+    keyboard
+        .write_events(&[
+            InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed),
+            InputEvent::syn_report(),
+        ])
+        .unwrap();
+    mouse
+        .write_events(&[
+            InputEvent::new(&TimeVal::new(0, 0), &EventCode::EV_REL(EV_REL::REL_X), 5),
+            InputEvent::syn_report(),
+        ])
+        .unwrap();
+
+    let timeout = Some(std::time::Duration::from_secs(1));
+    let mut events = Vec::new();
+    while let Some(ev) = reader.next_event(timeout).unwrap() {
+        events.push((ev.source, ev.event.event_code));
+        if events.len() == 4 {
+            break;
+        }
+    }
+    events.sort();
+    assert_eq!(
+        events,
+        vec![
+            (keyboard_token, EventCode::EV_KEY(EV_KEY::KEY_A)),
+            (keyboard_token, EventCode::EV_SYN(EV_SYN::SYN_REPORT)),
+            (mouse_token, EventCode::EV_REL(EV_REL::REL_X)),
+            (mouse_token, EventCode::EV_SYN(EV_SYN::SYN_REPORT)),
+        ]
+    );
+    assert_eq!(
+        reader.next_event(Some(std::time::Duration::ZERO)).unwrap(),
+        None
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn device_event_stream() {
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let d = Device::new_from_path(keyboard.devnode().unwrap()).unwrap();
+    let mut events = d.event_stream().unwrap();
+
+    let press = InputEvent::key(EV_KEY::KEY_A, KeyState::Pressed);
+    keyboard
+        .write_events(&[press.clone(), InputEvent::syn_report()])
+        .unwrap();
+
+    let ev = events.next_event().await.unwrap().unwrap();
+    assert_eq!((ev.event_code, ev.value), (press.event_code, press.value));
+    let ev = events.next_event().await.unwrap().unwrap();
+    assert_eq!(ev.event_code, EventCode::EV_SYN(EV_SYN::SYN_REPORT));
+}
+
+#[cfg(feature = "udev")]
+fn next_hotplug_event(monitor: &HotplugMonitor) -> HotplugEvent {
+    loop {
+        if let Some(event) = monitor.next_event().unwrap() {
+            return event;
+        }
+        let mut pollfd = libc::pollfd {
+            fd: monitor.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        assert_eq!(
+            unsafe { libc::poll(&mut pollfd, 1, 5000) },
+            1,
+            "no hotplug event"
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "udev")]
+fn hotplug_monitor() {
+    let monitor = HotplugMonitor::new().unwrap();
+
+    let keyboard = UInputDevice::virtual_keyboard().unwrap();
+    let devnode = std::path::PathBuf::from(keyboard.devnode().unwrap());
+    assert_eq!(
+        next_hotplug_event(&monitor),
+        HotplugEvent::Added(devnode.clone())
+    );
+    assert!(monitor.enumerate().unwrap().contains(&devnode));
+
+    drop(keyboard);
+    assert_eq!(next_hotplug_event(&monitor), HotplugEvent::Removed(devnode));
+}